@@ -0,0 +1,110 @@
+// Builds the static tables in src/precomputed.rs if the `precomputed` feature
+// is enabled. The tables are calculated with the same code the library uses
+// at runtime, so the modules that are needed for that are included here, but
+// only with the feature, so that other builds don't compile them twice.
+
+#[cfg(feature = "precomputed")]
+extern crate alloc;
+
+#[cfg(feature = "precomputed")]
+#[allow(dead_code)]
+#[path = "src/abbreviation.rs"]
+mod abbreviation;
+#[cfg(feature = "precomputed")]
+#[allow(dead_code)]
+#[path = "src/base.rs"]
+mod base;
+#[cfg(feature = "precomputed")]
+#[allow(dead_code)]
+#[path = "src/cache.rs"]
+mod cache;
+#[cfg(feature = "precomputed")]
+#[allow(dead_code)]
+#[path = "src/factor.rs"]
+mod factor;
+#[cfg(feature = "precomputed")]
+#[allow(dead_code)]
+#[path = "src/integer.rs"]
+mod integer;
+
+// there are no tables yet while they're being built
+#[cfg(feature = "precomputed")]
+mod precomputed {
+    pub(crate) fn smaller_factor(_n: u64) -> Option<u64> {
+        None
+    }
+
+    pub(crate) fn abbreviation(_n: usize) -> Option<&'static str> {
+        None
+    }
+
+    pub(crate) fn base(_abbr: &str) -> Option<usize> {
+        None
+    }
+
+    pub(crate) fn precomputed_limit() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(feature = "precomputed")]
+use std::{env, fmt::Write, fs, path::Path};
+
+#[cfg(feature = "precomputed")]
+const DEFAULT_LIMIT: u64 = 10_000;
+
+#[cfg(not(feature = "precomputed"))]
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+#[cfg(feature = "precomputed")]
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-env-changed=BASES_PRECOMPUTED_LIMIT");
+    let limit = match env::var("BASES_PRECOMPUTED_LIMIT") {
+        Ok(s) => s
+            .parse()
+            .ok()
+            .filter(|&n| n >= 1 && n <= u64::from(u32::MAX))
+            .unwrap_or_else(|| panic!("invalid BASES_PRECOMPUTED_LIMIT {:?}", s)),
+        Err(_) => DEFAULT_LIMIT,
+    };
+
+    let mut cache = cache::Cache::default();
+    let mut factors = String::new();
+    for n in 0..=limit {
+        let smaller = if n < 2 {
+            1
+        } else {
+            base::closest_factors(n, &mut cache).0
+        };
+        writeln!(factors, "    {},", smaller).unwrap();
+    }
+    // this also fills in the abbreviations of all smaller bases (including 0,
+    // which is never shown, but still takes up an abbreviation)
+    cache.abbreviation(limit);
+    let mut abbreviations = String::new();
+    for abbr in &cache.abbreviations.abbreviations {
+        writeln!(abbreviations, "    {:?},", abbr).unwrap();
+    }
+    let mut sorted: Vec<_> = cache.abbreviations.abbreviations.iter().zip(0..).collect();
+    sorted.sort_unstable();
+    let mut bases = String::new();
+    for (abbr, n) in sorted {
+        writeln!(bases, "    ({:?}, {}),", abbr, n).unwrap();
+    }
+
+    let len = limit + 1;
+    let code = format!(
+        "static SMALLER_FACTORS: [u32; {len}] = [\n{factors}];\n\
+        static ABBREVIATIONS: [&str; {len}] = [\n{abbreviations}];\n\
+        static BASES: [(&str, u32); {len}] = [\n{bases}];\n",
+        len = len,
+        factors = factors,
+        abbreviations = abbreviations,
+        bases = bases,
+    );
+    let out = Path::new(&env::var_os("OUT_DIR").unwrap()).join("precomputed.rs");
+    fs::write(out, code).unwrap();
+}
@@ -0,0 +1,301 @@
+use crate::{
+    base::{abbreviation_letters, Candidates},
+    cache::Cache,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+const DEFAULT_ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Which vowels are left out of abbreviations
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum VowelPolicy {
+    /// Keep every vowel of the name
+    Keep,
+    /// Leave out vowels after the given number of characters of the name,
+    /// e.g. with `DropAfter(3)`, abbreviations for "hexadecimal" are made from
+    /// the letters HEXDCML
+    DropAfter(usize),
+}
+
+impl Default for VowelPolicy {
+    fn default() -> Self {
+        Self::DropAfter(3)
+    }
+}
+
+/// Settings for how abbreviations are chosen, see
+/// `Cache::set_abbreviation_options`
+///
+/// Each abbreviation is made from the first letter of the uppercase name of
+/// a base, followed by as few of its other letters as possible (but at least
+/// enough to reach the minimum length), so that it differs from the
+/// abbreviations of all smaller bases. The default options give the usual
+/// abbreviations, e.g. DEC for decimal. If every candidate is taken, which
+/// can only happen with custom options, the base is abbreviated as its
+/// number, followed by as many apostrophes as needed if that's reserved.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AbbreviationOptions {
+    pub(crate) min_len: usize,
+    pub(crate) vowels: VowelPolicy,
+    pub(crate) reserved: Vec<String>,
+    pub(crate) alphabet: String,
+}
+
+impl Default for AbbreviationOptions {
+    fn default() -> Self {
+        Self {
+            min_len: 3,
+            vowels: VowelPolicy::default(),
+            reserved: Vec::new(),
+            alphabet: DEFAULT_ALPHABET.to_string(),
+        }
+    }
+}
+
+impl AbbreviationOptions {
+    /// Sets the minimum length of abbreviations (3 by default, values below
+    /// 1 are treated as 1)
+    ///
+    /// Abbreviations can still be shorter than this if the name of a base is
+    /// too short.
+    pub fn min_len(mut self, min_len: usize) -> Self {
+        self.min_len = min_len.max(1);
+        self
+    }
+
+    /// Sets which vowels are left out of abbreviations
+    pub fn vowels(mut self, vowels: VowelPolicy) -> Self {
+        self.vowels = vowels;
+        self
+    }
+
+    /// Adds an abbreviation that must never be used, e.g. a word that would
+    /// be offensive or a symbol that's already used for something else
+    pub fn reserve(mut self, abbr: impl Into<String>) -> Self {
+        self.reserved.push(abbr.into());
+        self
+    }
+
+    /// Sets the characters that abbreviations can be made of (A to Z by
+    /// default); other characters of the name are skipped
+    ///
+    /// Names are uppercase when they're abbreviated, so lowercase characters
+    /// in the alphabet are never used.
+    pub fn alphabet(mut self, alphabet: impl Into<String>) -> Self {
+        self.alphabet = alphabet.into();
+        self
+    }
+
+    pub(crate) fn is_default(&self) -> bool {
+        self.min_len == 3
+            && self.vowels == VowelPolicy::default()
+            && self.reserved.is_empty()
+            && self.alphabet == DEFAULT_ALPHABET
+    }
+
+    // The characters of an uppercase name that abbreviations can be made of
+    pub(crate) fn letters(&self, name: &str) -> String {
+        name.char_indices()
+            .filter(|(_, c)| self.alphabet.contains(*c))
+            .filter(|(i, c)| match self.vowels {
+                VowelPolicy::Keep => true,
+                VowelPolicy::DropAfter(n) => *i < n || !"AEIOU".contains(*c),
+            })
+            .map(|(_, c)| c)
+            .collect()
+    }
+
+    pub(crate) fn is_reserved(&self, abbr: &str) -> bool {
+        self.reserved.iter().any(|r| r == abbr)
+    }
+}
+
+/// An abbreviation that couldn't be used because it was already taken
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Collision {
+    /// The abbreviation that was rejected
+    pub candidate: String,
+    /// The smaller base that already uses it, or `None` if it's reserved (see
+    /// `AbbreviationOptions::reserve`)
+    pub owner: Option<i64>,
+}
+
+/// A row of an abbreviation table, see `abbreviation_table`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AbbreviationEntry {
+    /// The base
+    pub base: i64,
+    /// The abbreviation of the base
+    pub abbreviation: String,
+    /// Every abbreviation that was tried before the chosen one, in order
+    pub collisions: Vec<Collision>,
+}
+
+/// Get the abbreviations of every base from 1 up to `limit`, along with the
+/// candidates that were rejected for each one because they were taken
+///
+/// This explains why some abbreviations are longer or less obvious than
+/// others, e.g. 585 is BAKR because BAK is already used by 13.
+pub fn abbreviation_table(limit: i64, cache: &mut Cache) -> Vec<AbbreviationEntry> {
+    if limit < 1 {
+        return Vec::new();
+    }
+    // this fills in the abbreviations of every base up to the limit
+    cache.abbreviation(limit as u64);
+    let options = cache.abbreviations.options.clone();
+    let mut res = Vec::new();
+    for n in 1..=limit {
+        let abbreviation = cache.abbreviation(n as u64).to_string();
+        // every candidate before the chosen one must have been taken
+        let letters = abbreviation_letters(n as u64, &mut cache.tables, &options);
+        let table = &cache.abbreviations;
+        let collisions = Candidates::new(&letters, options.min_len)
+            .take_while(|candidate| *candidate != abbreviation)
+            .map(|candidate| Collision {
+                owner: table.base(&candidate).map(|m| m as i64).filter(|&m| m < n),
+                candidate,
+            })
+            .collect();
+        res.push(AbbreviationEntry {
+            base: n,
+            abbreviation,
+            collisions,
+        });
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{base_abbreviation, base_from_abbreviation, Cache};
+    use std::collections::HashSet;
+
+    fn abbreviations(options: AbbreviationOptions, limit: i64) -> Vec<String> {
+        let mut cache = Cache::default();
+        cache.set_abbreviation_options(options);
+        let res: Vec<_> = (1..=limit)
+            .map(|n| base_abbreviation(n, &mut cache))
+            .collect();
+        assert_eq!(res.iter().collect::<HashSet<_>>().len(), res.len());
+        res
+    }
+
+    #[test]
+    fn default_options() {
+        assert!(AbbreviationOptions::default().is_default());
+        assert!(!AbbreviationOptions::default().min_len(2).is_default());
+        assert_eq!(
+            AbbreviationOptions::default().letters("BAKER'S DOZENAL"),
+            "BAKRSDZNL"
+        );
+        let mut cache = Cache::default();
+        let expected = abbreviations(AbbreviationOptions::default(), 1000);
+        cache.set_abbreviation_options(AbbreviationOptions::default().min_len(3));
+        for (n, abbr) in (1..).zip(expected) {
+            assert_eq!(base_abbreviation(n, &mut cache), abbr);
+        }
+    }
+
+    #[test]
+    fn custom_options() {
+        let short = abbreviations(AbbreviationOptions::default().min_len(2), 1000);
+        assert_eq!(short[..6], ["UN", "BI", "TR", "QU", "QI", "SE"]);
+        let single = abbreviations(AbbreviationOptions::default().min_len(1), 10);
+        assert_eq!(single[..4], ["U", "B", "T", "Q"]);
+
+        let vowels = abbreviations(
+            AbbreviationOptions::default()
+                .min_len(4)
+                .vowels(VowelPolicy::Keep),
+            1000,
+        );
+        assert_eq!(vowels[5], "SEXI");
+
+        let reserved = abbreviations(AbbreviationOptions::default().reserve("SEX"), 1000);
+        assert_eq!(reserved[5], "SEM");
+        assert!(!reserved.contains(&"SEX".to_string()));
+
+        let alphabet = abbreviations(
+            AbbreviationOptions::default().alphabet("ABCDEFGHIJKLMNOPQRSTUVWYZ"),
+            1000,
+        );
+        assert_eq!(alphabet[5], "SEM");
+        assert!(alphabet.iter().all(|abbr| !abbr.contains('X')));
+    }
+
+    #[test]
+    fn exhausted_candidates() {
+        // names without any of these letters fall back to the number
+        let abbrs = abbreviations(AbbreviationOptions::default().alphabet("BX"), 20);
+        assert_eq!(abbrs[..3], ["1", "B", "3"]);
+        assert_eq!(abbrs[5], "X");
+        assert_eq!(abbrs[15], "16");
+
+        let options = AbbreviationOptions::default().alphabet("BX");
+        let abbrs = abbreviations(options.reserve("16").reserve("16'"), 20);
+        assert_eq!(abbrs[15], "16''");
+    }
+
+    #[test]
+    fn lookup_with_options() {
+        let mut cache = Cache::default();
+        cache.set_abbreviation_options(AbbreviationOptions::default().reserve("SEX"));
+        assert_eq!(base_from_abbreviation("SEX", 100, &mut cache), None);
+        assert_eq!(base_from_abbreviation("SEM", 100, &mut cache), Some(6));
+        assert_eq!(base_abbreviation(10, &mut cache), "DEC");
+
+        // changing the options forgets the old abbreviations
+        cache.set_abbreviation_options(AbbreviationOptions::default());
+        assert_eq!(cache.stats().abbreviation_entries, 0);
+        assert_eq!(base_abbreviation(6, &mut cache), "SEX");
+    }
+
+    #[test]
+    fn collision_report() {
+        let mut cache = Cache::default();
+        let table = abbreviation_table(1000, &mut cache);
+        assert_eq!(table.len(), 1000);
+        assert!(abbreviation_table(0, &mut cache).is_empty());
+        for entry in &table {
+            assert_eq!(
+                base_abbreviation(entry.base, &mut cache),
+                entry.abbreviation
+            );
+            for collision in &entry.collisions {
+                let owner = collision.owner.unwrap();
+                assert!(owner < entry.base);
+                assert_eq!(table[owner as usize - 1].abbreviation, collision.candidate);
+            }
+        }
+        assert!(table[9].collisions.is_empty());
+        assert_eq!(
+            table[14].collisions,
+            [Collision {
+                candidate: "TRI".to_string(),
+                owner: Some(3)
+            }]
+        );
+        let bakr = &table[584].collisions;
+        assert_eq!(
+            (bakr[0].candidate.as_str(), bakr[0].owner),
+            ("BAK", Some(13))
+        );
+        assert_eq!(bakr.len(), 36);
+        assert_eq!(table[840].collisions.len(), 99);
+
+        cache.set_abbreviation_options(AbbreviationOptions::default().reserve("SEX"));
+        let table = abbreviation_table(6, &mut cache);
+        assert_eq!(
+            table[5].collisions,
+            [Collision {
+                candidate: "SEX".to_string(),
+                owner: None
+            }]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    base::{factor_pairs, num_roots_in_name, Base, BaseName},
+    cache::{Cache, Memo},
+};
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// One possible name for a base
+pub struct AlternativeName {
+    /// The name
+    pub name: BaseName,
+    /// The number of roots needed to name the factors, as compared when
+    /// choosing between factor pairs (the same count as
+    /// `Factorisation::root_count`, so "hen" and "sna" aren't counted). For
+    /// bases that aren't split into factors, the number of roots in the name.
+    pub root_count: usize,
+}
+
+/// Iterator over the possible names of a base, see `alternative_names`
+pub struct AlternativeNames(vec::IntoIter<AlternativeName>);
+
+impl Iterator for AlternativeNames {
+    type Item = AlternativeName;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Get every valid name for a given number base
+///
+/// The first name is always the one returned by `base_name`. It's followed by
+/// one name for each other way of splitting the base into two factors (e.g.
+/// "decahexagesimal" for 600), ordered by the number of roots and then by how
+/// close together the factors are. The factors themselves are always named
+/// as usual.
+pub fn alternative_names(n: i64, cache: &mut Cache) -> AlternativeNames {
+    alternative_names_with_memo(n, cache)
+}
+
+pub(crate) fn alternative_names_with_memo(n: i64, cache: &mut impl Memo<u64>) -> AlternativeNames {
+    let mut names = unsigned_alternative_names(n.unsigned_abs(), cache);
+    if n < 0 {
+        for alt in &mut names {
+            let base = core::mem::replace(&mut alt.name.0, Base::Nullary);
+            alt.name.0 = Base::Nega(Box::new(base));
+        }
+    }
+    AlternativeNames(names.into_iter())
+}
+
+fn unsigned_alternative_names(n: u64, cache: &mut impl Memo<u64>) -> Vec<AlternativeName> {
+    let canonical = Base::new_unsigned(n, cache);
+    let mut names = vec![AlternativeName {
+        root_count: num_roots_in_name(n, false, cache),
+        name: BaseName(canonical.clone(), true),
+    }];
+    if n < 2 {
+        return names;
+    }
+    let mut others = vec![];
+    for (smaller, larger, root_count) in factor_pairs(n, cache) {
+        let base = Base::FactorPair(
+            Box::new(Base::new_unsigned(smaller, cache)),
+            Box::new(Base::new_unsigned(larger, cache)),
+        );
+        if base == canonical {
+            names[0].root_count = root_count;
+            continue;
+        }
+        others.push(AlternativeName {
+            root_count,
+            name: BaseName(base, true),
+        });
+    }
+    // stable, so pairs with the same number of roots stay closest first
+    others.sort_by_key(|alt| alt.root_count);
+    names.extend(others);
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(n: i64) -> Vec<(String, usize)> {
+        let mut cache = Cache::default();
+        alternative_names(n, &mut cache)
+            .map(|alt| (alt.name.to_string(), alt.root_count))
+            .collect()
+    }
+
+    #[test]
+    fn composite() {
+        let names = names(600);
+        assert_eq!(names[0], ("hexacentesimal".to_string(), 2));
+        assert!(names.contains(&("decahexagesimal".to_string(), 3)));
+        assert_eq!(names.len(), 11);
+        assert!(names[1..].windows(2).all(|w| w[0].1 <= w[1].1));
+    }
+
+    #[test]
+    fn canonical_first() {
+        // the same counts as `explain`, where 19 * 34 wins the tie-break
+        let names = names(646);
+        assert_eq!(
+            names,
+            vec![
+                ("hentrihexasnabisuboptimal".to_string(), 5),
+                ("malbintriseximal".to_string(), 5),
+                ("bimaluntriseximal".to_string(), 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn same_counts_as_explain() {
+        let mut cache = Cache::default();
+        for n in 2..1000 {
+            let e = crate::explain(n, &mut cache);
+            let counts: Vec<_> = alternative_names(n, &mut cache)
+                .map(|alt| alt.root_count)
+                .collect();
+            if let Some(chosen) = e.chosen {
+                assert_eq!(counts[0], e.candidates[chosen].root_count, "{}", n);
+                assert!(counts.iter().all(|&c| c >= counts[0]), "{}", n);
+            }
+        }
+    }
+
+    #[test]
+    fn roots_and_primes() {
+        assert_eq!(
+            names(12),
+            vec![
+                ("dozenal".to_string(), 1),
+                ("triquaternary".to_string(), 2),
+                ("biseximal".to_string(), 2),
+            ]
+        );
+        assert_eq!(names(19), vec![("untriseximal".to_string(), 3)]);
+        assert_eq!(names(1), vec![("unary".to_string(), 1)]);
+        assert_eq!(names(-6)[0], ("negaseximal".to_string(), 1));
+        assert_eq!(names(-6)[1], ("negabitrinary".to_string(), 2));
+    }
+}
@@ -1,11 +1,23 @@
-use std::{
-    collections::{HashMap, HashSet},
-    convert, fmt,
+use crate::{
+    abbreviation::AbbreviationOptions,
+    cache::{AbbreviationTable, Memo},
+    factor::{prime_factors, small_divisors, small_divisors_from, Divisors},
+    integer::BaseInteger,
+    precomputed,
 };
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{convert, error, fmt, iter};
 
+/// One of the root numbers that have their own names
 #[repr(u8)]
-#[derive(Clone, Copy)]
-pub(crate) enum Root {
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Root {
     Binary = 2,
     Trinary = 3,
     Quaternary = 4,
@@ -26,7 +38,29 @@ pub(crate) enum Root {
 }
 
 impl Root {
-    fn from_number(number: i64) -> Option<Self> {
+    /// All roots, in ascending order
+    pub const ALL: [Self; 17] = [
+        Self::Binary,
+        Self::Trinary,
+        Self::Quaternary,
+        Self::Quinary,
+        Self::Seximal,
+        Self::Septimal,
+        Self::Octal,
+        Self::Nonary,
+        Self::Decimal,
+        Self::Elevenary,
+        Self::Dozenal,
+        Self::BakersDozenal,
+        Self::Hex,
+        Self::Suboptimal,
+        Self::Vigesimal,
+        Self::Niftimal,
+        Self::Centesimal,
+    ];
+
+    /// Returns the root for the given number, if there is one
+    pub fn from_number(number: i64) -> Option<Self> {
         Some(match number {
             2 => Self::Binary,
             3 => Self::Trinary,
@@ -49,30 +83,8 @@ impl Root {
         })
     }
 
-    fn parse(s: &str) -> Option<Self> {
-        Some(match s {
-            "binary" => Self::Binary,
-            "trinary" => Self::Trinary,
-            "quaternary" => Self::Quaternary,
-            "quinary" => Self::Quinary,
-            "seximal" => Self::Seximal,
-            "septimal" => Self::Septimal,
-            "octal" => Self::Octal,
-            "nonary" => Self::Nonary,
-            "decimal" => Self::Decimal,
-            "elevenary" => Self::Elevenary,
-            "dozenal" => Self::Dozenal,
-            "baker's dozenal" => Self::BakersDozenal,
-            "hex" => Self::Hex,
-            "suboptimal" => Self::Suboptimal,
-            "vigesimal" => Self::Vigesimal,
-            "niftimal" => Self::Niftimal,
-            "centesimal" => Self::Centesimal,
-            _ => return None,
-        })
-    }
-
-    fn name(&self) -> &'static str {
+    /// The name of this root on its own, e.g. "decimal"
+    pub fn name(&self) -> &'static str {
         match self {
             Self::Binary => "binary",
             Self::Trinary => "trinary",
@@ -94,7 +106,8 @@ impl Root {
         }
     }
 
-    fn prefix_name(&self) -> &'static str {
+    /// The name of this root when used as a prefix, e.g. "deca"
+    pub fn prefix_name(&self) -> &'static str {
         match self {
             Self::Binary => "bi",
             Self::Trinary => "tri",
@@ -116,7 +129,8 @@ impl Root {
         }
     }
 
-    fn suffix_name(&self) -> &'static str {
+    /// The name of this root when used after a prefix, e.g. "gesimal"
+    pub fn suffix_name(&self) -> &'static str {
         match self {
             Self::Decimal => "gesimal",
             Self::BakersDozenal => "ker's dozenal",
@@ -124,207 +138,583 @@ impl Root {
         }
     }
 
-    fn to_number(self) -> u8 {
+    /// The number this root stands for
+    pub fn to_number(self) -> u8 {
         self as u8
     }
 }
 
-fn num_roots_in_name(n: i64, prefix: bool, cache: &mut Cache) -> usize {
-    if n < 0 {
-        panic!()
+fn root_from_number<T: BaseInteger>(n: &T) -> Option<Root> {
+    Root::from_number(convert::TryFrom::try_from(n.to_u64()?).ok()?)
+}
+
+pub(crate) fn num_roots_in_name<T: BaseInteger>(
+    n: T,
+    prefix: bool,
+    cache: &mut impl Memo<T>,
+) -> usize {
+    if n.to_u64().is_some_and(|n| n <= 1) {
+        return 1;
     }
-    if n == 1 {
+    if root_from_number(&n).is_some() {
         return 1;
     }
-    if Root::from_number(n).is_some() {
-        1
+    let key = (n, prefix);
+    if let Some(res) = cache.root_count(&key) {
+        return res;
+    }
+    let (a, b) = closest_factors(key.0.clone(), cache);
+    let res = if a.to_u64() == Some(1) {
+        if prefix {
+            2 + num_roots_in_name(key.0.minus_one(), true, cache)
+        } else {
+            1 + num_roots_in_name(key.0.minus_one(), false, cache)
+        }
     } else {
-        let (a, b) = closest_factors(n, cache);
-        if a == 1 {
-            if prefix {
-                2 + num_roots_in_name(n - 1, true, cache)
-            } else {
-                1 + num_roots_in_name(n - 1, false, cache)
+        num_roots_in_name(a, true, cache) + num_roots_in_name(b, prefix, cache)
+    };
+    cache.insert_root_count(key, res);
+    res
+}
+
+fn abbr_in_use(abbr: &str, table: &AbbreviationTable) -> bool {
+    table.bases.contains_key(abbr) || table.options.is_reserved(abbr)
+}
+
+// Appends the letters of the name at the given positions, which are sorted
+// (positions past the end of the name are ignored)
+fn get_abbr(name: &[char], positions: &[usize], res: &mut String) {
+    res.extend(positions.iter().map_while(|&i| name.get(i)));
+}
+
+// Moves to the next set of positions of the same size, in the order of the
+// numbers that have bits set at those positions (e.g. 0b011, 0b101, 0b110,
+// 0b1001), which works for names of any length
+fn next_positions(positions: &mut [usize]) {
+    for i in 0..positions.len() {
+        // move the first position that can move up by one, and move all the
+        // positions before it back to the start
+        if i + 1 == positions.len() || positions[i] + 1 != positions[i + 1] {
+            positions[i] += 1;
+            for (j, p) in positions[..i].iter_mut().enumerate() {
+                *p = j;
             }
-        } else {
-            num_roots_in_name(a, true, cache) + num_roots_in_name(b, prefix, cache)
+            return;
         }
     }
 }
 
-fn sqrt(n: i64) -> i64 {
-    if n < 2 {
-        return n;
+pub(crate) fn find_abbreviation<'a>(
+    n: u64,
+    cache: &mut impl Memo<u64>,
+    table: &'a mut AbbreviationTable,
+) -> &'a str {
+    let n: usize = convert::TryFrom::try_from(n).unwrap();
+    let precomputed = table.uses_precomputed();
+    if let Some(abbr) = precomputed::abbreviation(n).filter(|_| precomputed) {
+        return abbr;
+    }
+    for num in table.abbreviations.len()..=n {
+        if let Some(abbr) = precomputed::abbreviation(num).filter(|_| precomputed) {
+            table.push(abbr.to_string());
+            continue;
+        }
+        let letters = abbreviation_letters(num as u64, cache, &table.options);
+        let abbr = Candidates::new(&letters, table.options.min_len)
+            .find(|abbr| !abbr_in_use(abbr, table))
+            // every candidate is taken, this can only happen with custom
+            // options. The number can only be taken if it's reserved, so
+            // apostrophes are added until it's free.
+            .unwrap_or_else(|| {
+                iter::successors(Some(num.to_string()), |abbr| Some(format!("{}'", abbr)))
+                    .find(|abbr| !abbr_in_use(abbr, table))
+                    .unwrap()
+            });
+        table.push(abbr);
     }
-    let mut a = 1255;
-    let mut b = n / a;
-    a = (a + b) / 2;
-    b = n / a;
-    a = (a + b) / 2;
-    b = n / a;
-    a = (a + b) / 2;
-    b = n / a;
-    a = (a + b) / 2;
-    a + 1
+    table.abbreviations[n].as_str()
 }
 
-fn abbr_in_use(abbr: &str, cache: &mut Cache) -> bool {
-    cache.abbr_set.contains(abbr)
+// The letters of the name of n that abbreviations can be made of
+pub(crate) fn abbreviation_letters(
+    n: u64,
+    cache: &mut impl Memo<u64>,
+    options: &AbbreviationOptions,
+) -> String {
+    let mut name = BaseName(Base::new_unsigned(n, cache), true).to_string();
+    name.make_ascii_uppercase();
+    options.letters(&name)
 }
 
-fn get_abbr(name: &str, k: u64, res: &mut String) {
-    for (_, ch) in name.char_indices().filter(|(i, _)| k & (1 << i) != 0) {
-        res.push(ch);
-    }
+// Every possible abbreviation made from the given letters, in the order in
+// which they're tried: the first letter followed by as few of the others as
+// possible, preferring earlier letters
+pub(crate) struct Candidates {
+    first_char: Option<char>,
+    // the letters after the first one
+    name: Vec<char>,
+    abbr_len: usize,
+    max_len: usize,
+    // the positions of the letters of `name` used by the previous candidate
+    // of this length
+    positions: Option<Vec<usize>>,
+    first: bool,
 }
 
-// from https://stackoverflow.com/a/8281965
-fn next_bitstring(v: u64) -> u64 {
-    let t = v | v.wrapping_sub(1);
-    t.wrapping_add(1) | ((!t & (!t).wrapping_neg()).wrapping_sub(1) >> (v.trailing_zeros() + 1))
+impl Candidates {
+    pub(crate) fn new(letters: &str, min_len: usize) -> Self {
+        let mut chars = letters.chars();
+        let first_char = chars.next();
+        let name: Vec<char> = chars.collect();
+        Self {
+            first_char,
+            abbr_len: min_len,
+            max_len: min_len.max(name.len() + 1),
+            name,
+            positions: None,
+            first: true,
+        }
+    }
 }
 
-pub(crate) fn find_abbreviation(n: i64, cache: &mut Cache) -> &str {
-    assert!(n >= 1);
-    let n: usize = convert::TryFrom::try_from(n).unwrap();
-    for num in cache.abbreviations.len()..=n {
-        let num: i64 = convert::TryFrom::try_from(num).unwrap();
-        let mut name = BaseName(Base::new(num, cache), true).to_string();
-        name.make_ascii_uppercase();
-        name = name
-            .char_indices()
-            .filter(|(_, c)| *c != ' ' && *c != '\'')
-            .filter(|(i, c)| *i < 3 || !"AEIOU".contains(*c))
-            .map(|(_, c)| c)
-            .collect();
-        let first_char = name.chars().next().unwrap();
-        let mut abbr = String::from(first_char);
-        let name = name.split_at(1).1;
-        let mut first = true;
-        'outer: for abbr_len in 3.. {
-            let mut k = (1 << (abbr_len - 1)) - 1;
-            loop {
-                if cfg!(test) {
-                    assert_eq!(u64::count_ones(k), abbr_len - 1);
-                }
-                if !first && k & (1 << (name.len() as u32 - 1)) != 0 {
-                    break;
+impl Iterator for Candidates {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let (first_char, len) = (self.first_char?, self.name.len());
+        while self.abbr_len <= self.max_len {
+            let positions = match &mut self.positions {
+                None => self.positions.insert((0..self.abbr_len - 1).collect()),
+                Some(positions) if positions.is_empty() => {
+                    self.abbr_len += 1;
+                    self.positions = None;
+                    continue;
                 }
-                get_abbr(&name, k, &mut abbr);
-                first = false;
-                if !abbr_in_use(&abbr, cache) {
-                    break 'outer;
+                Some(positions) => {
+                    next_positions(positions);
+                    positions
                 }
-                abbr.clear();
-                abbr.push(first_char);
-                k = next_bitstring(k);
+            };
+            // the last letter is never used, except if the first candidate
+            // is the whole name
+            let uses_last = positions.last().is_some_and(|&p| p + 1 >= len);
+            if !self.first && uses_last {
+                self.abbr_len += 1;
+                self.positions = None;
+                continue;
             }
+            self.first = false;
+            let mut abbr = String::from(first_char);
+            get_abbr(&self.name, positions, &mut abbr);
+            return Some(abbr);
         }
-        cache.abbreviations.push(abbr.clone());
-        cache.abbr_set.insert(abbr);
+        None
     }
-    cache.abbreviations[n].as_str()
 }
 
-// input: >= 2
-// output: (1.., 2..)
-fn closest_factors(n: i64, cache: &mut Cache) -> (i64, i64) {
-    if n < 2 {
-        panic!()
+// Negative bases are abbreviated like their absolute value with a minus sign,
+// which can't collide with positive bases because those only use letters
+pub(crate) fn integer_abbreviation(
+    n: i64,
+    cache: &mut impl Memo<u64>,
+    table: &mut AbbreviationTable,
+) -> String {
+    let abbr = find_abbreviation(n.unsigned_abs(), cache, table);
+    if n < 0 {
+        format!("-{}", abbr)
+    } else {
+        abbr.to_string()
+    }
+}
+
+// Fractional bases are abbreviated as "numerator/denominator", e.g. UNA/DEC
+// for votdecimal, with the same restrictions as their names (so the sign of a
+// negative numerator moves to the denominator)
+pub(crate) fn rational_abbreviation(
+    num: i64,
+    den: i64,
+    cache: &mut impl Memo<u64>,
+    table: &mut AbbreviationTable,
+) -> Result<String, NameError> {
+    if den == 0 {
+        Err(NameError::ZeroDenominator)
+    } else if den == 1 {
+        Ok(integer_abbreviation(num, cache, table))
+    } else if num == 0 {
+        Err(NameError::InvalidNumerator)
+    } else {
+        let num_abbr = find_abbreviation(num.unsigned_abs(), cache, table).to_string();
+        if num < 0 && den == -1 {
+            return Ok(num_abbr);
+        }
+        let sign = if (num < 0) != (den < 0) { "-" } else { "" };
+        Ok(format!(
+            "{}/{}{}",
+            num_abbr,
+            sign,
+            find_abbreviation(den.unsigned_abs(), cache, table)
+        ))
     }
-    if let Some(res) = cache.factors.get(&n) {
-        return *res;
+}
+
+// Non-rational bases are abbreviated as their custom name with a tilde, e.g.
+// ~PHI for phinary. This doesn't use the `AbbreviationOptions`, because there
+// are no other abbreviations that the name has to be shortened to avoid.
+pub(crate) fn non_rational_abbreviation(name: &str) -> String {
+    format!("~{}", name.to_uppercase())
+}
+
+// Returns the integer base with the given abbreviation, calculating
+// abbreviations up to `limit` (in absolute value) until it's found
+pub(crate) fn find_base(
+    abbr: &str,
+    limit: i64,
+    cache: &mut impl Memo<u64>,
+    table: &mut AbbreviationTable,
+) -> Option<i64> {
+    let limit: usize = convert::TryFrom::try_from(limit).ok()?;
+    let (abbr, negative) = match abbr.strip_prefix('-') {
+        Some(abbr) => (abbr, true),
+        None => (abbr, false),
+    };
+    let found = |n: usize| match (n as i64, negative) {
+        _ if n > limit => None,
+        (0, true) => None,
+        (n, true) => Some(-n),
+        (n, false) => Some(n),
+    };
+    // bases in the precomputed table are only added to `table` once the
+    // first base after them is needed
+    let mut first = 0;
+    if table.uses_precomputed() {
+        if let Some(n) = precomputed::base(abbr) {
+            return found(n);
+        }
+        first = precomputed::precomputed_limit().map_or(0, |n| n as usize + 1);
     }
-    let mut res = (1, n);
-    let mut root_count = usize::MAX;
-    let loop_max = sqrt(n);
-    for smaller_factor in (2..loop_max.min(n)).rev() {
-        if n % smaller_factor != 0 {
-            continue;
+    loop {
+        if let Some(&n) = table.bases.get(abbr) {
+            return found(n);
         }
-        let larger_factor = n / smaller_factor;
-        let (smaller_factor, larger_factor) = if larger_factor < smaller_factor {
-            (larger_factor, smaller_factor)
-        } else {
-            (smaller_factor, larger_factor)
-        };
-        let this_root_count = num_roots_in_name(smaller_factor, false, cache)
-            + num_roots_in_name(larger_factor, false, cache);
-        // if n == 646 {
-        //     eprintln!("{} = {} * {} ({} roots)", n, smaller_factor, larger_factor, this_root_count);
-        // }
-        if this_root_count > root_count {
-            continue;
+        let next = table.abbreviations.len().max(first);
+        if next > limit {
+            return None;
         }
+        find_abbreviation(next as u64, cache, table);
+    }
+}
+
+// Returns every factor pair of n as (smaller, larger, number of roots),
+// starting with the factors that are closest together
+pub(crate) fn factor_pairs<T: BaseInteger>(n: T, cache: &mut impl Memo<T>) -> Vec<(T, T, usize)> {
+    let divisors = small_divisors(&n);
+    factor_pairs_from(n, divisors, cache)
+}
+
+fn factor_pairs_from<T: BaseInteger>(
+    n: T,
+    small_divisors: Vec<T>,
+    cache: &mut impl Memo<T>,
+) -> Vec<(T, T, usize)> {
+    let mut res = vec![];
+    for smaller_factor in small_divisors {
+        let larger_factor = n.div_by(&smaller_factor);
+        let root_count = num_roots_in_name(smaller_factor.clone(), false, cache)
+            + num_roots_in_name(larger_factor.clone(), false, cache);
+        res.push((smaller_factor, larger_factor, root_count));
+    }
+    res
+}
+
+// input: >= 2 (smaller numbers have no factors and return (1, n))
+// output: (1.., 2..)
+pub(crate) fn closest_factors<T: BaseInteger>(n: T, cache: &mut impl Memo<T>) -> (T, T) {
+    if let Some(smaller) = n.to_u64().and_then(precomputed::smaller_factor) {
+        let smaller = T::from_u64(smaller);
+        let larger = n.div_by(&smaller);
+        return (smaller, larger);
+    }
+    if let Some(res) = cache.factors(&n) {
+        return res;
+    }
+    let primes = prime_factors(&n);
+    let divisor_count = primes
+        .iter()
+        .try_fold(1usize, |count, (_, k)| count.checked_mul(*k as usize + 1));
+    if divisor_count.is_some_and(|count| count >= DIVISOR_TABLE_MIN) {
+        return closest_factors_of_divisors(&primes, cache);
+    }
+    let mut res = (T::from_u64(1), n.clone());
+    let mut root_count = usize::MAX;
+    let divisors = small_divisors_from(&n, &primes);
+    for (smaller_factor, larger_factor, this_root_count) in
+        factor_pairs_from(n.clone(), divisors, cache)
+    {
+        // ties go to the earlier pair, i.e. the one with closer factors
         if this_root_count < root_count {
             root_count = this_root_count;
             res = (smaller_factor, larger_factor);
         }
-        if larger_factor - smaller_factor < res.1 - res.0 {
-            res = (smaller_factor, larger_factor)
+    }
+    cache.insert_factors(n, res.clone());
+    res
+}
+
+// Numbers with at least this many divisors are handled by
+// `closest_factors_of_divisors`
+const DIVISOR_TABLE_MIN: usize = 256;
+
+// Finds the closest factors of every divisor of a number, given its prime
+// factors, and returns those of the number itself
+//
+// This gives the same results as calling `closest_factors` on each divisor,
+// but keeps the root counts of the divisors in a table instead of looking
+// them up in the cache for every factor pair. That matters for numbers with
+// many divisors, e.g. 897612484786617600 has 103680 divisors, which have
+// about 80 million factor pairs between them.
+fn closest_factors_of_divisors<T: BaseInteger>(
+    primes: &[(T, u32)],
+    cache: &mut impl Memo<T>,
+) -> (T, T) {
+    let divisors = Divisors::new(primes);
+    let values = &divisors.values;
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].cmp(&values[b]));
+    let mut rank = vec![0; values.len()];
+    for (i, &d) in order.iter().enumerate() {
+        rank[d] = i;
+    }
+    // the root counts of each divisor as the last factor and as a prefix
+    let mut root_counts = vec![(1, 1); values.len()];
+    let mut res = (T::from_u64(1), T::from_u64(1));
+    // every factor pair is made of smaller divisors, so they come first
+    for &d in &order[1..] {
+        // (root count, index of the smaller factor), with ties going to the
+        // larger smaller factor like in `closest_factors`
+        let mut best: Option<(usize, usize)> = None;
+        divisors.for_each_divisor(d, |a| {
+            if a == 0 || rank[a] > rank[d - a] {
+                return;
+            }
+            let count = root_counts[a].0 + root_counts[d - a].0;
+            if best.is_none_or(|(best_count, best_a)| {
+                count < best_count || (count == best_count && rank[a] > rank[best_a])
+            }) {
+                best = Some((count, a));
+            }
+        });
+        let n = &values[d];
+        let smaller = best.map_or(0, |(_, a)| a);
+        res = (values[smaller].clone(), values[d - smaller].clone());
+        cache.insert_factors(n.clone(), res.clone());
+        if root_from_number(n).is_some() {
+            continue;
         }
+        root_counts[d] = if smaller == 0 {
+            (
+                1 + num_roots_in_name(n.minus_one(), false, cache),
+                2 + num_roots_in_name(n.minus_one(), true, cache),
+            )
+        } else {
+            let (a, b) = (root_counts[smaller], root_counts[d - smaller]);
+            (a.1 + b.0, a.1 + b.1)
+        };
+        cache.insert_root_count((n.clone(), false), root_counts[d].0);
+        cache.insert_root_count((n.clone(), true), root_counts[d].1);
     }
-    cache.factors.insert(n, res);
     res
 }
 
-/// Used to cache intermediate calculations
-#[derive(Default)]
-pub struct Cache {
-    factors: HashMap<i64, (i64, i64)>,
-    abbreviations: Vec<String>,
-    abbr_set: HashSet<String>,
+/// Error returned when a base can't be named
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum NameError {
+    /// Fractional bases can't have a numerator of zero, because the numerator
+    /// is named with a prefix and there's no prefix for zero (the sign of a
+    /// negative numerator moves to the denominator instead)
+    InvalidNumerator,
+    /// Fractional bases can't have a denominator of zero
+    ZeroDenominator,
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumerator => write!(f, "numerator must not be zero"),
+            Self::ZeroDenominator => write!(f, "denominator must not be zero"),
+        }
+    }
+}
+
+impl error::Error for NameError {}
+
+/// The value of a base
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BaseValue {
+    /// An integer base, e.g. 10 for "decimal"
+    Integer(i64),
+    /// A fractional base as numerator and denominator, in lowest terms with a
+    /// positive denominator, e.g. (2, 3) for "bivottrinary"
+    Rational(i64, i64),
+    /// A non-rational base, with its custom name, e.g. "phi" for "phinary"
+    NonRational(String),
 }
 
-pub(crate) enum Base {
+/// The structure of a base name
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum Base {
+    /// Base zero ("nullary")
     Nullary,
+    /// Base one ("unary")
     Unary,
+    /// A root number, e.g. "seximal"
     Root(Root),
+    /// The product of two factors, e.g. "hexagesimal" (6 * 10)
     FactorPair(Box<Base>, Box<Base>),
-    Prime(Box<Base>), // un- prefix
+    /// One more than the given base, used for primes ("un-" or "hen-...-sna")
+    Prime(Box<Base>),
+    /// The negative of the given base ("nega-")
     Nega(Box<Base>),
+    /// The given numerator divided by the given denominator ("vot-")
     Vot(Box<Base>, Box<Base>),
+    /// A non-rational base less than six, e.g. "phinary"
     CustomLessThanSix(String),
-    Imal(String), // greater than six, one syllable
-    Al(String),   // greater than six, more than one syllable
+    /// A non-rational base greater than six with a one-syllable name, e.g. "tauimal"
+    Imal(String),
+    /// A non-rational base greater than six with a longer name
+    Al(String),
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+// returns None if the denominator is zero
+fn reduce_fraction(num: i128, den: i128) -> Option<(i128, i128)> {
+    if den == 0 {
+        return None;
+    }
+    let (num, den) = if den < 0 {
+        (num.checked_neg()?, den.checked_neg()?)
+    } else {
+        (num, den)
+    };
+    let divisor = gcd(num.unsigned_abs(), den.unsigned_abs()) as i128;
+    Some((num / divisor, den / divisor))
 }
 
-fn is_vowel_or_y(ch: char) -> bool {
+pub(crate) fn is_vowel_or_y(ch: char) -> bool {
     matches!(ch, 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
 }
 
 impl Base {
-    pub(crate) fn new_frac(num: i64, den: i64, cache: &mut Cache) -> Self {
-        if den == 1 {
-            Self::new(num, cache)
+    /// Returns the root, if this base is a root number
+    pub fn root(&self) -> Option<Root> {
+        match self {
+            Self::Root(r) => Some(*r),
+            _ => None,
+        }
+    }
+
+    /// Returns the two factors, if this base is a factor pair
+    pub fn factors(&self) -> Option<(&Base, &Base)> {
+        match self {
+            Self::FactorPair(a, b) => Some((a, b)),
+            _ => None,
+        }
+    }
+
+    /// Returns the base that is one less than this one, if this is a prime base
+    pub fn one_below(&self) -> Option<&Base> {
+        match self {
+            Self::Prime(one_below) => Some(one_below),
+            _ => None,
+        }
+    }
+
+    /// Returns the negated base, if this is a negative base
+    pub fn negated(&self) -> Option<&Base> {
+        match self {
+            Self::Nega(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// Returns the numerator and denominator, if this is a fractional base
+    pub fn fraction(&self) -> Option<(&Base, &Base)> {
+        match self {
+            Self::Vot(num, den) => Some((num, den)),
+            _ => None,
+        }
+    }
+
+    /// Returns the custom name, if this is a non-rational base
+    pub fn custom_name(&self) -> Option<&str> {
+        match self {
+            Self::CustomLessThanSix(s) | Self::Imal(s) | Self::Al(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn new_frac(
+        num: i64,
+        den: i64,
+        cache: &mut impl Memo<u64>,
+    ) -> Result<Self, NameError> {
+        if den == 0 {
+            Err(NameError::ZeroDenominator)
+        } else if den == 1 {
+            Ok(Self::new(num, cache))
+        } else if num == 0 {
+            // the numerator is used as a prefix, and there's no prefix for zero
+            Err(NameError::InvalidNumerator)
         } else {
-            Self::Vot(
-                Box::new(Self::new(num, cache)),
-                Box::new(Self::new(den, cache)),
-            )
+            // there are no prefixes for negative numbers either, so the sign
+            // of a negative numerator moves to the denominator (using absolute
+            // values so that `i64::MIN` doesn't overflow)
+            let num_base = Self::new_unsigned(num.unsigned_abs(), cache);
+            if num < 0 && den == -1 {
+                return Ok(num_base);
+            }
+            let mut den_base = Self::new_unsigned(den.unsigned_abs(), cache);
+            if (num < 0) != (den < 0) {
+                den_base = Self::Nega(Box::new(den_base));
+            }
+            Ok(Self::Vot(Box::new(num_base), Box::new(den_base)))
         }
     }
 
-    pub(crate) fn new(n: i64, cache: &mut Cache) -> Self {
+    pub(crate) fn new(n: i64, cache: &mut impl Memo<u64>) -> Self {
         if n < 0 {
-            return Self::Nega(Box::new(Self::new(-n, cache)));
+            // `unsigned_abs` instead of `-n` so that `i64::MIN` doesn't overflow
+            Self::Nega(Box::new(Self::new_unsigned(n.unsigned_abs(), cache)))
+        } else {
+            Self::new_unsigned(n as u64, cache)
         }
-        if n == 0 {
-            return Self::Nullary;
-        };
-        if n == 1 {
-            return Self::Unary;
-        };
-        if let Some(root) = Root::from_number(n) {
+    }
+
+    pub(crate) fn new_unsigned<T: BaseInteger>(n: T, cache: &mut impl Memo<T>) -> Self {
+        match n.to_u64() {
+            Some(0) => return Self::Nullary,
+            Some(1) => return Self::Unary,
+            _ => (),
+        }
+        if let Some(root) = root_from_number(&n) {
             return Self::Root(root);
         }
         let (a, b) = closest_factors(n, cache);
-        if a == 1 {
+        if a.to_u64() == Some(1) {
             // prime base
-            return Self::Prime(Box::new(Base::new(b - 1, cache)));
+            return Self::Prime(Box::new(Base::new_unsigned(b.minus_one(), cache)));
         }
-        let a = Base::new(a, cache);
-        let b = Base::new(b, cache);
+        let a = Base::new_unsigned(a, cache);
+        let b = Base::new_unsigned(b, cache);
         Self::FactorPair(Box::new(a), Box::new(b))
     }
 
@@ -338,32 +728,72 @@ impl Base {
         }
     }
 
-    pub(crate) fn try_parse(s: &str) -> Option<Self> {
-        Some(Self::Root(Root::parse(s)?))
+    /// Returns the value of this base, or `None` if it doesn't fit in an
+    /// `i64` or divides by zero
+    pub fn value(&self) -> Option<BaseValue> {
+        if let Some(name) = self.non_rational_name() {
+            return Some(BaseValue::NonRational(name.to_string()));
+        }
+        let (num, den) = self.fraction_value()?;
+        let num = convert::TryFrom::try_from(num).ok()?;
+        let den = convert::TryFrom::try_from(den).ok()?;
+        Some(if den == 1 {
+            BaseValue::Integer(num)
+        } else {
+            BaseValue::Rational(num, den)
+        })
+    }
+
+    /// Returns the value of this base if it's an integer that fits in an `i64`
+    pub fn to_integer(&self) -> Option<i64> {
+        match self.value()? {
+            BaseValue::Integer(n) => Some(n),
+            _ => None,
+        }
     }
 
-    pub(crate) fn to_number(&self) -> i64 {
+    // the first custom name in this base, if any
+    fn non_rational_name(&self) -> Option<&str> {
         match self {
-            Self::Nullary => 0,
-            Self::Unary => 1,
-            Self::Root(r) => r.to_number().into(),
-            Self::FactorPair(a, b) => a.to_number() * b.to_number(),
-            Self::Prime(one_below) => one_below.to_number() + 1,
-            Self::Nega(n) => -n.to_number(),
-            Self::Vot(num, den) => {
-                if den.to_number() == 1 {
-                    num.to_number()
-                } else {
-                    panic!("non-integer base")
-                }
+            Self::Nullary | Self::Unary | Self::Root(_) => None,
+            Self::FactorPair(a, b) | Self::Vot(a, b) => {
+                a.non_rational_name().or_else(|| b.non_rational_name())
+            }
+            Self::Prime(n) | Self::Nega(n) => n.non_rational_name(),
+            Self::CustomLessThanSix(s) | Self::Imal(s) | Self::Al(s) => Some(s),
+        }
+    }
+
+    // numerator and denominator in lowest terms, for bases without custom names
+    // (using i128 so that e.g. `i64::MIN` doesn't overflow before it's negated)
+    pub(crate) fn fraction_value(&self) -> Option<(i128, i128)> {
+        match self {
+            Self::Nullary => Some((0, 1)),
+            Self::Unary => Some((1, 1)),
+            Self::Root(r) => Some((r.to_number().into(), 1)),
+            Self::FactorPair(a, b) => {
+                let (a_num, a_den) = a.fraction_value()?;
+                let (b_num, b_den) = b.fraction_value()?;
+                reduce_fraction(a_num.checked_mul(b_num)?, a_den.checked_mul(b_den)?)
+            }
+            Self::Prime(one_below) => {
+                let (num, den) = one_below.fraction_value()?;
+                Some((num.checked_add(den)?, den))
+            }
+            Self::Nega(n) => {
+                let (num, den) = n.fraction_value()?;
+                Some((num.checked_neg()?, den))
+            }
+            Self::Vot(a, b) => {
+                let (a_num, a_den) = a.fraction_value()?;
+                let (b_num, b_den) = b.fraction_value()?;
+                reduce_fraction(a_num.checked_mul(b_den)?, a_den.checked_mul(b_num)?)
             }
-            Self::CustomLessThanSix(_) => panic!("unknown number"),
-            Self::Imal(_) => panic!("unknown number"),
-            Self::Al(_) => panic!("unknown number"),
+            Self::CustomLessThanSix(_) | Self::Imal(_) | Self::Al(_) => None,
         }
     }
 
-    fn prefix_name(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fn prefix_name<W: fmt::Write>(&self, f: &mut W) -> fmt::Result {
         match self {
             Self::Root(r) => write!(f, "{}", r.prefix_name()),
             Self::Prime(one_below) => {
@@ -375,18 +805,19 @@ impl Base {
                 a.prefix_name(f)?;
                 b.prefix_name(f)
             }
-            _ => panic!(),
+            // can't happen for bases created by this crate
+            _ => Err(fmt::Error),
         }
     }
 
-    fn suffix_name(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fn suffix_name<W: fmt::Write>(&self, f: &mut W) -> fmt::Result {
         match self {
             Self::Root(r) => write!(f, "{}", r.suffix_name()),
             _ => self.format_name(f),
         }
     }
 
-    pub(crate) fn format_name(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    pub(crate) fn format_name<W: fmt::Write>(&self, f: &mut W) -> fmt::Result {
         match self {
             Self::Nullary => write!(f, "nullary"),
             Self::Unary => write!(f, "unary"),
@@ -404,7 +835,7 @@ impl Base {
                 n.format_name(f)
             }
             Self::Vot(a, b) => {
-                if a.to_number() != 1 {
+                if a.value() != Some(BaseValue::Integer(1)) {
                     a.prefix_name(f)?;
                 }
                 write!(f, "vot")?;
@@ -436,57 +867,118 @@ enum EndVowel {
     O,
 }
 
-fn fixup_vowels(s: &str) -> String {
-    let mut res = String::new();
-    let mut prev = None;
-    for ch in s.chars() {
-        match (prev, ch) {
-            (Some('i'), 'i' | 'u') => {
-                res.push('i');
-                prev = None;
-            }
-            (Some('a' | 'o'), 'o' | 'e' | 'i' | 'u') => {
-                res.push(ch);
-                prev = None;
-            }
-            (Some(p), _) => {
-                res.push(p);
-                prev = Some(ch);
-            }
-            (None, _) => prev = Some(ch),
+// Merges vowels at morpheme boundaries while the name is being written, so
+// that e.g. "tetra" + "octal" becomes "tetroctal". The last letter is held
+// back until the next one is known, so `finish` has to be called at the end.
+struct FixupVowels<'a, W> {
+    inner: &'a mut W,
+    prev: Option<char>,
+}
+
+impl<'a, W: fmt::Write> FixupVowels<'a, W> {
+    fn new(inner: &'a mut W) -> Self {
+        Self { inner, prev: None }
+    }
+
+    fn finish(self) -> fmt::Result {
+        match self.prev {
+            Some(p) => self.inner.write_char(p),
+            None => Ok(()),
         }
     }
-    if let Some(p) = prev {
-        res.push(p);
+}
+
+impl<W: fmt::Write> fmt::Write for FixupVowels<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for ch in s.chars() {
+            match (self.prev, ch) {
+                (Some('i'), 'i' | 'u') => {
+                    self.inner.write_char('i')?;
+                    self.prev = None;
+                }
+                (Some('a' | 'o'), 'o' | 'e' | 'i' | 'u') => {
+                    self.inner.write_char(ch)?;
+                    self.prev = None;
+                }
+                (Some(p), _) => {
+                    self.inner.write_char(p)?;
+                    self.prev = Some(ch);
+                }
+                (None, _) => self.prev = Some(ch),
+            }
+        }
+        Ok(())
     }
-    res
 }
 
-struct InternalName<'a>(&'a Base);
+// A writer into a fixed-size buffer, see `BaseName::write_to_slice`
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
 
-impl fmt::Display for InternalName<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.format_name(f)
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len.checked_add(s.len()).ok_or(fmt::Error)?;
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
     }
 }
 
 /// Represents the name of a number base (bool: fixup)
 pub struct BaseName(pub(crate) Base, pub(crate) bool);
 
-impl fmt::Display for BaseName {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = InternalName(&self.0).to_string();
+impl BaseName {
+    /// The structure of this name
+    pub fn base(&self) -> &Base {
+        &self.0
+    }
+
+    /// Converts this name into its structure
+    pub fn into_base(self) -> Base {
+        self.0
+    }
+
+    /// Writes this name to the given writer, without allocating
+    ///
+    /// This produces the same text as `to_string`, but can write into any
+    /// `fmt::Write`, e.g. a fixed-size buffer or a writer that streams the
+    /// text somewhere else.
+    pub fn write_to<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
         if self.1 {
-            write!(f, "{}", fixup_vowels(&s))
+            let mut w = FixupVowels::new(w);
+            self.0.format_name(&mut w)?;
+            w.finish()
         } else {
-            write!(f, "{}", &s)
+            self.0.format_name(w)
         }
     }
+
+    /// Writes this name into the start of the given buffer and returns it,
+    /// or returns an error if the buffer is too small
+    pub fn write_to_slice<'a>(&self, buf: &'a mut [u8]) -> Result<&'a str, fmt::Error> {
+        let mut w = SliceWriter { buf, len: 0 };
+        self.write_to(&mut w)?;
+        let SliceWriter { buf, len } = w;
+        // only whole strings were copied into the buffer
+        core::str::from_utf8(&buf[..len]).map_err(|_| fmt::Error)
+    }
+}
+
+impl fmt::Display for BaseName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cache::Cache;
     use std::fmt;
 
     struct PrefixName(Base);
@@ -548,6 +1040,29 @@ mod tests {
         check_name(8 * 19, "octuntriseximal", &mut cache);
     }
 
+    #[test]
+    fn write_to_buffers() {
+        let mut cache = Cache::default();
+        let mut buf = [0; 128];
+        for n in -100..3000 {
+            let name = BaseName(Base::new(n, &mut cache), true);
+            let expected = name.to_string();
+            let mut s = String::new();
+            name.write_to(&mut s).unwrap();
+            assert_eq!(s, expected);
+            assert_eq!(name.write_to_slice(&mut buf), Ok(&expected[..]));
+        }
+
+        let name = BaseName(Base::new(646, &mut cache), true);
+        let mut exact = [0; 25];
+        assert_eq!(
+            name.write_to_slice(&mut exact),
+            Ok("hentrihexasnabisuboptimal")
+        );
+        assert_eq!(name.write_to_slice(&mut exact[..24]), Err(fmt::Error));
+        assert_eq!(name.write_to_slice(&mut []), Err(fmt::Error));
+    }
+
     #[test]
     fn special_names() {
         let mut cache = Cache::default();
@@ -557,6 +1072,51 @@ mod tests {
         check_name(-10, "negadecimal", &mut cache);
     }
 
+    #[test]
+    fn values() {
+        let mut cache = Cache::default();
+        let value = |base: Base| base.value();
+        assert_eq!(
+            value(Base::new(646, &mut cache)),
+            Some(BaseValue::Integer(646))
+        );
+        assert_eq!(
+            value(Base::new(-10, &mut cache)),
+            Some(BaseValue::Integer(-10))
+        );
+        assert_eq!(
+            value(Base::new_frac(2, 4, &mut cache).unwrap()),
+            Some(BaseValue::Rational(1, 2))
+        );
+        assert_eq!(
+            value(Base::new_frac(2, -3, &mut cache).unwrap()),
+            Some(BaseValue::Rational(-2, 3))
+        );
+        assert_eq!(
+            value(Base::new_frac(6, 3, &mut cache).unwrap()),
+            Some(BaseValue::Integer(2))
+        );
+        assert_eq!(
+            Base::new_frac(2, 0, &mut cache),
+            Err(NameError::ZeroDenominator)
+        );
+        assert_eq!(
+            Base::new_frac(0, 5, &mut cache),
+            Err(NameError::InvalidNumerator)
+        );
+        let votnullary = Base::Vot(Box::new(Base::Root(Root::Binary)), Box::new(Base::Nullary));
+        assert_eq!(value(votnullary), None);
+        assert_eq!(
+            value(Base::new_custom("phi", false, true)),
+            Some(BaseValue::NonRational("phi".to_string()))
+        );
+        let mut huge = Base::Root(Root::Centesimal);
+        for _ in 0..10 {
+            huge = Base::FactorPair(Box::new(Base::Root(Root::Centesimal)), Box::new(huge));
+        }
+        assert_eq!(value(huge), None);
+    }
+
     #[test]
     fn long_names() {
         let mut cache = Cache::default();
@@ -589,45 +1149,108 @@ mod tests {
         check_name(7220, "tetrahentrihexasnapentuntriseximal", &mut cache);
     }
 
+    #[test]
+    fn many_divisors() {
+        // every proper divisor of these has fewer than `DIVISOR_TABLE_MIN`
+        // divisors, so they're named the usual way
+        for &n in &[1_441_440, (1 << 15) * 3u64.pow(15), 110_880 * 1_000_003] {
+            let mut cache = Cache::default();
+            assert_eq!(
+                closest_factors_of_divisors(&prime_factors(&n), &mut cache),
+                closest_factors(n, &mut cache)
+            );
+            let mut expected = Cache::default();
+            let (a, b, _) = factor_pairs(n, &mut expected)
+                .into_iter()
+                .min_by_key(|&(_, _, root_count)| root_count)
+                .unwrap();
+            assert_eq!(closest_factors(n, &mut cache), (a, b));
+            for d in small_divisors(&n).into_iter().flat_map(|d| [d, n / d]) {
+                assert_eq!(
+                    closest_factors(d, &mut cache),
+                    closest_factors(d, &mut expected)
+                );
+            }
+            for prefix in [false, true] {
+                assert_eq!(
+                    num_roots_in_name(n, prefix, &mut cache),
+                    num_roots_in_name(a, true, &mut expected)
+                        + num_roots_in_name(b, prefix, &mut expected)
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_get_abbr() {
-        fn get_abbr_test(s: &str, k: u64) -> String {
+        fn get_abbr_test(s: &str, positions: &[usize]) -> String {
+            let chars: Vec<char> = s.chars().collect();
             let mut res = String::new();
-            get_abbr(s, k, &mut res);
+            get_abbr(&chars, positions, &mut res);
             res
         }
-        assert_eq!(get_abbr_test("abcdefg", 0), "");
-        assert_eq!(get_abbr_test("abcdefg", 1), "a");
-        assert_eq!(get_abbr_test("abcdefg", 2), "b");
-        assert_eq!(get_abbr_test("abcdefg", 3), "ab");
-        assert_eq!(get_abbr_test("abcdefg", 4), "c");
-        assert_eq!(get_abbr_test("abcdefg", 5), "ac");
-        assert_eq!(get_abbr_test("abcdefg", 6), "bc");
-        assert_eq!(get_abbr_test("abcdefg", 7), "abc");
-        assert_eq!(get_abbr_test("abcdefg", 8), "d");
+        assert_eq!(get_abbr_test("abcdefg", &[]), "");
+        assert_eq!(get_abbr_test("abcdefg", &[0]), "a");
+        assert_eq!(get_abbr_test("abcdefg", &[1]), "b");
+        assert_eq!(get_abbr_test("abcdefg", &[0, 1]), "ab");
+        assert_eq!(get_abbr_test("abcdefg", &[0, 2]), "ac");
+        assert_eq!(get_abbr_test("abcdefg", &[1, 2]), "bc");
+        assert_eq!(get_abbr_test("abcdefg", &[0, 1, 2]), "abc");
+        assert_eq!(get_abbr_test("abcdefg", &[3, 10]), "d");
+    }
+
+    #[test]
+    fn next_positions_order() {
+        // the next larger number with the same number of set bits, from
+        // https://stackoverflow.com/a/8281965
+        fn next_bitstring(v: u64) -> u64 {
+            let t = v | v.wrapping_sub(1);
+            t.wrapping_add(1)
+                | ((!t & (!t).wrapping_neg()).wrapping_sub(1) >> (v.trailing_zeros() + 1))
+        }
+
+        for len in 1..6 {
+            let mut positions: Vec<usize> = (0..len).collect();
+            let mut k = (1 << len) - 1;
+            while k < 1 << 20 {
+                let bits = positions.iter().map(|&p| 1 << p).sum::<u64>();
+                assert_eq!(bits, k);
+                next_positions(&mut positions);
+                k = next_bitstring(k);
+            }
+        }
     }
 
     #[test]
-    fn next_bitstr() {
-        assert_eq!(next_bitstring(0b11), 0b101);
+    fn long_abbreviation_candidates() {
+        // more letters than there are bits in a u64
+        let letters: String = (0..100).map(|i| (b'A' + i % 26) as char).collect();
+        let candidates: Vec<_> = Candidates::new(&letters, 3).take(4754).collect();
+        assert_eq!(candidates[..3], ["ABC", "ABD", "ACD"]);
+        // every pair of the 98 letters after the first one that doesn't use
+        // the last letter, then the first candidate with three letters
+        assert_eq!(candidates[4752], "ATU");
+        assert_eq!(candidates[4753], "ABCD");
+        // the first letter, then the letters at positions 0 and 80 of the rest
+        assert_eq!(candidates[3160], "ABD");
     }
 
     #[test]
     fn find_abbr() {
         let mut cache = Cache::default();
-        assert_eq!(find_abbreviation(1, &mut cache), "UNA");
-        assert_eq!(find_abbreviation(16, &mut cache), "HEX");
-        assert_eq!(find_abbreviation(40, &mut cache), "PEC");
-        assert_eq!(find_abbreviation(100, &mut cache), "CEN");
-        assert_eq!(find_abbreviation(200, &mut cache), "DEV");
-        assert_eq!(find_abbreviation(300, &mut cache), "TCN");
-        assert_eq!(find_abbreviation(400, &mut cache), "ICO");
-        assert_eq!(find_abbreviation(500, &mut cache), "PCN");
-        assert_eq!(find_abbreviation(585, &mut cache), "BAKR");
-        assert_eq!(find_abbreviation(841, &mut cache), "HSS");
-        assert_eq!(find_abbreviation(969, &mut cache), "HBM");
-        assert_eq!(find_abbreviation(1000, &mut cache), "DCS");
-        //assert_eq!(find_abbreviation(5758, &mut cache), "BBBC");
-        //assert_eq!(find_abbreviation(6254, &mut cache), "HHTK");
+        assert_eq!(cache.abbreviation(1), "UNA");
+        assert_eq!(cache.abbreviation(16), "HEX");
+        assert_eq!(cache.abbreviation(40), "PEC");
+        assert_eq!(cache.abbreviation(100), "CEN");
+        assert_eq!(cache.abbreviation(200), "DEV");
+        assert_eq!(cache.abbreviation(300), "TCN");
+        assert_eq!(cache.abbreviation(400), "ICO");
+        assert_eq!(cache.abbreviation(500), "PCN");
+        assert_eq!(cache.abbreviation(585), "BAKR");
+        assert_eq!(cache.abbreviation(841), "HSS");
+        assert_eq!(cache.abbreviation(969), "HBM");
+        assert_eq!(cache.abbreviation(1000), "DCS");
+        assert_eq!(cache.abbreviation(5758), "BBBC");
+        assert_eq!(cache.abbreviation(6254), "HHTK");
     }
 }
@@ -0,0 +1,63 @@
+use crate::integer::{private, BaseInteger};
+use core::convert::TryFrom;
+use num_bigint::BigUint;
+
+impl private::Sealed for BigUint {}
+
+impl BaseInteger for BigUint {
+    fn from_u64(n: u64) -> Self {
+        n.into()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        u64::try_from(self).ok()
+    }
+
+    fn divides(&self, n: &Self) -> bool {
+        (n % self).bits() == 0
+    }
+
+    fn div_by(&self, divisor: &Self) -> Self {
+        self / divisor
+    }
+
+    fn minus_one(&self) -> Self {
+        self - 1u32
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn rem(&self, m: &Self) -> Self {
+        self % m
+    }
+
+    fn abs_diff(&self, other: &Self) -> Self {
+        if self >= other {
+            self - other
+        } else {
+            other - self
+        }
+    }
+
+    fn add_mod(&self, other: &Self, m: &Self) -> Self {
+        (self + other) % m
+    }
+
+    fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+        self * other % m
+    }
+
+    fn bits(&self) -> u64 {
+        BigUint::bits(self)
+    }
+
+    fn bit(&self, i: u64) -> bool {
+        BigUint::bit(self, i)
+    }
+
+    fn shr(&self, k: u64) -> Self {
+        self >> k
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{
+    abbreviation::AbbreviationOptions,
+    base::{find_abbreviation, find_base, integer_abbreviation, rational_abbreviation, NameError},
+    integer::BaseInteger,
+    precomputed,
+};
+use alloc::{string::String, vec::Vec};
+use core::hash::Hash;
+
+// without `std` there's no hasher, so memoized results are kept in B-trees
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::BTreeMap as Map;
+#[cfg(feature = "std")]
+pub(crate) use std::collections::HashMap as Map;
+
+// Storage for memoized results, implemented by `Cache` and `SyncCache`
+pub(crate) trait Memo<T> {
+    fn factors(&mut self, n: &T) -> Option<(T, T)>;
+    fn insert_factors(&mut self, n: T, factors: (T, T));
+    fn root_count(&mut self, key: &(T, bool)) -> Option<usize>;
+    fn insert_root_count(&mut self, key: (T, bool), count: usize);
+}
+
+/// How much a `Cache` or `SyncCache` is allowed to remember
+///
+/// This limits the number of memoized factors and root counts, not the memory
+/// they take, which depends on the integer type. The abbreviation table is
+/// never limited: abbreviations are never forgotten, because each one depends
+/// on all the abbreviations before it, so it grows with the largest base that
+/// was abbreviated or looked up by abbreviation.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum CachePolicy {
+    /// Remember everything
+    #[default]
+    Unbounded,
+    /// Remember at most `max_entries` factor pairs and `max_entries` root
+    /// counts, forgetting the least recently used ones first. Each entry takes
+    /// roughly 50 bytes for `u64` bases, and more for larger integer types.
+    Lru {
+        /// The maximum number of entries in each table
+        max_entries: usize,
+    },
+}
+
+impl CachePolicy {
+    pub(crate) fn max_len(self) -> Option<usize> {
+        match self {
+            Self::Unbounded => None,
+            Self::Lru { max_entries } => Some(max_entries),
+        }
+    }
+}
+
+// A map that forgets its least recently used entries once it's full
+pub(crate) struct MemoMap<K, V> {
+    // the values, and when they were last used
+    map: Map<K, (V, u64)>,
+    clock: u64,
+    max_len: Option<usize>,
+    pub(crate) evictions: u64,
+}
+
+impl<K, V> Default for MemoMap<K, V> {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl<K, V> MemoMap<K, V> {
+    pub(crate) fn new(max_len: Option<usize>) -> Self {
+        Self {
+            map: Map::new(),
+            clock: 0,
+            max_len,
+            evictions: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.map.iter().map(|(k, (v, _))| (k, v))
+    }
+}
+
+impl<K: Hash + Ord, V> MemoMap<K, V> {
+    pub(crate) fn get(&mut self, key: &K) -> Option<&V> {
+        self.clock += 1;
+        let (value, last_used) = self.map.get_mut(key)?;
+        *last_used = self.clock;
+        Some(value)
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        if self.max_len == Some(0) {
+            return;
+        }
+        self.clock += 1;
+        self.map.insert(key, (value, self.clock));
+        if let Some(max_len) = self.max_len {
+            if self.map.len() > max_len {
+                self.evict(max_len);
+            }
+        }
+    }
+
+    // Evicts entries until only three quarters of `max_len` are left, so
+    // that the cost of finding the oldest entries is spread over many inserts
+    fn evict(&mut self, max_len: usize) {
+        let keep = max_len - max_len / 4;
+        let mut times: Vec<u64> = self.map.values().map(|(_, t)| *t).collect();
+        let remove = times.len() - keep;
+        // times are unique, so this keeps exactly `keep` entries
+        let (_, &mut oldest_kept, _) = times.select_nth_unstable(remove);
+        self.map.retain(|_, (_, t)| *t >= oldest_kept);
+        self.evictions += remove as u64;
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Tables<T> {
+    pub(crate) factors: MemoMap<T, (T, T)>,
+    pub(crate) root_counts: MemoMap<(T, bool), usize>,
+    stats: CacheStats,
+}
+
+impl<T> Tables<T> {
+    fn with_policy(policy: CachePolicy) -> Self {
+        Self {
+            factors: MemoMap::new(policy.max_len()),
+            root_counts: MemoMap::new(policy.max_len()),
+            stats: CacheStats::default(),
+        }
+    }
+}
+
+impl<T: BaseInteger> Memo<T> for Tables<T> {
+    fn factors(&mut self, n: &T) -> Option<(T, T)> {
+        let res = self.factors.get(n).cloned();
+        if res.is_some() {
+            self.stats.factor_hits += 1;
+        } else {
+            self.stats.factor_misses += 1;
+        }
+        res
+    }
+
+    fn insert_factors(&mut self, n: T, factors: (T, T)) {
+        self.factors.insert(n, factors);
+    }
+
+    fn root_count(&mut self, key: &(T, bool)) -> Option<usize> {
+        let res = self.root_counts.get(key).copied();
+        if res.is_some() {
+            self.stats.root_count_hits += 1;
+        } else {
+            self.stats.root_count_misses += 1;
+        }
+        res
+    }
+
+    fn insert_root_count(&mut self, key: (T, bool), count: usize) {
+        self.root_counts.insert(key, count);
+    }
+}
+
+// All abbreviations from 0 up to some n, in order, and the base of each one
+#[derive(Default)]
+pub(crate) struct AbbreviationTable {
+    pub(crate) abbreviations: Vec<String>,
+    pub(crate) bases: Map<String, usize>,
+    pub(crate) options: AbbreviationOptions,
+}
+
+impl AbbreviationTable {
+    pub(crate) fn new(options: AbbreviationOptions) -> Self {
+        Self {
+            abbreviations: Vec::new(),
+            bases: Map::new(),
+            options,
+        }
+    }
+
+    // the precomputed abbreviations were chosen with the default options
+    pub(crate) fn uses_precomputed(&self) -> bool {
+        self.options.is_default()
+    }
+
+    // The base with the given abbreviation, if it's in this table or in the
+    // precomputed table that it continues from
+    pub(crate) fn base(&self, abbr: &str) -> Option<usize> {
+        let precomputed = || precomputed::base(abbr).filter(|_| self.uses_precomputed());
+        self.bases.get(abbr).copied().or_else(precomputed)
+    }
+
+    // Adds the abbreviation of the next base, which must not be used by any
+    // smaller base
+    pub(crate) fn push(&mut self, abbr: String) {
+        debug_assert!(!self.bases.contains_key(&abbr), "{} is already used", abbr);
+        self.bases.insert(abbr.clone(), self.abbreviations.len());
+        self.abbreviations.push(abbr);
+    }
+}
+
+/// Used to cache intermediate calculations
+///
+/// The type parameter is the integer type of the bases being named, see
+/// `unsigned_base_name`.
+#[derive(Default)]
+pub struct Cache<T = u64> {
+    pub(crate) tables: Tables<T>,
+    pub(crate) abbreviations: AbbreviationTable,
+}
+
+impl<T> Cache<T> {
+    /// Creates an empty cache that remembers as much as the given policy allows
+    pub fn with_policy(policy: CachePolicy) -> Self {
+        Self {
+            tables: Tables::with_policy(policy),
+            abbreviations: AbbreviationTable::default(),
+        }
+    }
+
+    /// Changes how abbreviations are chosen for this cache. Abbreviations
+    /// that were already calculated with other options are forgotten.
+    pub fn set_abbreviation_options(&mut self, options: AbbreviationOptions) {
+        if options != self.abbreviations.options {
+            self.abbreviations = AbbreviationTable::new(options);
+        }
+    }
+
+    /// Returns how abbreviations are chosen for this cache
+    pub fn abbreviation_options(&self) -> &AbbreviationOptions {
+        &self.abbreviations.options
+    }
+
+    /// Returns how often the cache was used and how much it holds
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            factor_entries: self.tables.factors.len(),
+            root_count_entries: self.tables.root_counts.len(),
+            abbreviation_entries: self.abbreviations.abbreviations.len(),
+            evictions: self.tables.factors.evictions + self.tables.root_counts.evictions,
+            ..self.tables.stats
+        }
+    }
+}
+
+impl Cache {
+    pub(crate) fn abbreviation(&mut self, n: u64) -> &str {
+        find_abbreviation(n, &mut self.tables, &mut self.abbreviations)
+    }
+
+    pub(crate) fn integer_abbreviation(&mut self, n: i64) -> String {
+        integer_abbreviation(n, &mut self.tables, &mut self.abbreviations)
+    }
+
+    pub(crate) fn rational_abbreviation(
+        &mut self,
+        num: i64,
+        den: i64,
+    ) -> Result<String, NameError> {
+        rational_abbreviation(num, den, &mut self.tables, &mut self.abbreviations)
+    }
+
+    pub(crate) fn base_from_abbreviation(&mut self, abbr: &str, limit: i64) -> Option<i64> {
+        find_base(abbr, limit, &mut self.tables, &mut self.abbreviations)
+    }
+}
+
+impl<T: BaseInteger> Memo<T> for Cache<T> {
+    fn factors(&mut self, n: &T) -> Option<(T, T)> {
+        self.tables.factors(n)
+    }
+
+    fn insert_factors(&mut self, n: T, factors: (T, T)) {
+        self.tables.insert_factors(n, factors)
+    }
+
+    fn root_count(&mut self, key: &(T, bool)) -> Option<usize> {
+        self.tables.root_count(key)
+    }
+
+    fn insert_root_count(&mut self, key: (T, bool), count: usize) {
+        self.tables.insert_root_count(key, count)
+    }
+}
+
+/// Statistics about a `Cache` or `SyncCache`, see `Cache::stats`
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub struct CacheStats {
+    /// Number of times the best factors of a base were found in the cache
+    pub factor_hits: u64,
+    /// Number of times the best factors of a base had to be calculated
+    pub factor_misses: u64,
+    /// Number of bases whose best factors are cached
+    pub factor_entries: usize,
+    /// Number of times the number of roots in a name was found in the cache
+    pub root_count_hits: u64,
+    /// Number of times the number of roots in a name had to be calculated
+    pub root_count_misses: u64,
+    /// Number of root counts that are cached, counting prefixes and
+    /// standalone names separately
+    pub root_count_entries: usize,
+    /// Number of cached abbreviations (abbreviations are always calculated
+    /// in order, starting from 0)
+    pub abbreviation_entries: usize,
+    /// Number of entries that were forgotten because of the `CachePolicy`
+    pub evictions: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{base_abbreviation, base_name, precomputed::first_uncached};
+
+    #[test]
+    fn stats() {
+        let mut cache = Cache::default();
+        assert_eq!(cache.stats(), CacheStats::default());
+        let n = first_uncached() + 6254;
+        let name = base_name(n, &mut cache).to_string();
+        let stats = cache.stats();
+        assert!(stats.factor_misses > 0 && stats.root_count_misses > 0);
+        assert_eq!(stats.factor_entries as u64, stats.factor_misses);
+        assert_eq!(stats.root_count_entries as u64, stats.root_count_misses);
+        assert_eq!(stats.abbreviation_entries, 0);
+
+        // naming it again only needs cached results
+        assert_eq!(base_name(n, &mut cache).to_string(), name);
+        let again = cache.stats();
+        assert_eq!(again.factor_misses, stats.factor_misses);
+        assert_eq!(again.root_count_misses, stats.root_count_misses);
+        assert!(again.factor_hits > stats.factor_hits);
+
+        let n = first_uncached() + 10;
+        base_abbreviation(n, &mut cache);
+        assert_eq!(cache.stats().abbreviation_entries as i64, n + 1);
+
+        // whether or not they're precomputed, the results are the same
+        let name = "henbihentetraheptasnasnabintetraker's dozenal";
+        assert_eq!(base_name(6254, &mut cache).to_string(), name);
+        assert_eq!(base_abbreviation(10, &mut cache), "DEC");
+    }
+
+    #[test]
+    fn lru_policy() {
+        let mut cache = Cache::default();
+        let mut small = Cache::with_policy(CachePolicy::Lru { max_entries: 100 });
+        let mut empty = Cache::with_policy(CachePolicy::Lru { max_entries: 0 });
+        let start = first_uncached();
+        for n in start..start + 3000 {
+            let name = base_name(n, &mut cache).to_string();
+            assert_eq!(base_name(n, &mut small).to_string(), name);
+            assert_eq!(base_name(n, &mut empty).to_string(), name);
+            let stats = small.stats();
+            assert!(stats.factor_entries <= 100 && stats.root_count_entries <= 100);
+        }
+        assert!(small.stats().evictions > 0);
+        assert_eq!(cache.stats().evictions, 0);
+        assert_eq!(empty.stats().factor_entries, 0);
+    }
+
+    #[test]
+    fn lru_keeps_recent_entries() {
+        let mut map = MemoMap::new(Some(4));
+        for i in 0..4 {
+            map.insert(i, i);
+        }
+        // 0 is now the most recently used entry
+        assert_eq!(map.get(&0), Some(&0));
+        map.insert(4, 4);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.evictions, 2);
+        assert!(map.get(&0).is_some() && map.get(&4).is_some());
+        assert!(map.get(&1).is_none() && map.get(&2).is_none());
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{
+    base::{closest_factors, factor_pairs, Root},
+    cache::{Cache, Memo},
+};
+use alloc::{vec, vec::Vec};
+use core::{convert::TryFrom, fmt};
+
+/// A way of splitting a base into two factors
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Factorisation {
+    /// The smaller factor, used as a prefix
+    pub smaller: i64,
+    /// The larger factor
+    pub larger: i64,
+    /// The number of roots needed to name both factors
+    pub root_count: usize,
+}
+
+/// The rule that decided how a base is named
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Reason {
+    /// 0 and 1 are called "nullary" and "unary"
+    Special,
+    /// The base is one of the roots, so it has its own name
+    Root,
+    /// The base is prime, so it's named as one more than the base below it
+    Prime,
+    /// The chosen factorisation needs fewer roots than any other
+    FewestRoots,
+    /// Several factorisations need the fewest roots, and the chosen one has
+    /// the factors that are closest together
+    ClosestFactors,
+}
+
+/// Explains how the name of a base was chosen
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Explanation {
+    /// The base that was named
+    pub number: i64,
+    /// Whether the base is negative. Negative bases are named by adding "nega"
+    /// to the name of their absolute value, which the other fields explain.
+    pub negative: bool,
+    /// Every factorisation that was considered, closest factors first
+    pub candidates: Vec<Factorisation>,
+    /// The chosen factorisation (an index into `candidates`)
+    pub chosen: Option<usize>,
+    /// The rule that decided the name
+    pub reason: Reason,
+}
+
+/// Explain how the name of a given number base is chosen
+pub fn explain(n: i64, cache: &mut Cache) -> Explanation {
+    explain_with_memo(n, cache)
+}
+
+pub(crate) fn explain_with_memo(n: i64, cache: &mut impl Memo<u64>) -> Explanation {
+    let mut res = Explanation {
+        number: n,
+        negative: n < 0,
+        candidates: vec![],
+        chosen: None,
+        reason: Reason::Special,
+    };
+    let n = n.unsigned_abs();
+    if n <= 1 {
+        return res;
+    }
+    if i64::try_from(n).ok().and_then(Root::from_number).is_some() {
+        res.reason = Reason::Root;
+        return res;
+    }
+    let (smaller, larger) = closest_factors(n, cache);
+    for (i, (a, b, root_count)) in factor_pairs(n, cache).into_iter().enumerate() {
+        if (a, b) == (smaller, larger) {
+            res.chosen = Some(i);
+        }
+        // both factors are at most n / 2, so they fit in an i64
+        res.candidates.push(Factorisation {
+            smaller: a as i64,
+            larger: b as i64,
+            root_count,
+        });
+    }
+    res.reason = match res.chosen {
+        None => Reason::Prime,
+        Some(i) => {
+            let root_count = res.candidates[i].root_count;
+            let ties = res
+                .candidates
+                .iter()
+                .filter(|f| f.root_count == root_count)
+                .count();
+            if ties > 1 {
+                Reason::ClosestFactors
+            } else {
+                Reason::FewestRoots
+            }
+        }
+    };
+    res
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = self.number.unsigned_abs();
+        for (i, c) in self.candidates.iter().enumerate() {
+            write!(
+                f,
+                "{} = {} * {}: {} roots",
+                n, c.smaller, c.larger, c.root_count
+            )?;
+            if self.chosen == Some(i) {
+                write!(f, " (chosen)")?;
+            }
+            writeln!(f)?;
+        }
+        match self.reason {
+            Reason::Special => writeln!(f, "{} has a special name", n),
+            Reason::Root => writeln!(f, "{} is a root, so it has its own name", n),
+            Reason::Prime => writeln!(
+                f,
+                "{} is prime, so it's named after {} with \"un\" in front",
+                n,
+                n - 1
+            ),
+            Reason::FewestRoots => {
+                writeln!(f, "the chosen factors need the fewest roots")
+            }
+            Reason::ClosestFactors => writeln!(
+                f,
+                "several factor pairs need the fewest roots, \
+                so the one with the closest factors was chosen"
+            ),
+        }?;
+        if self.negative {
+            writeln!(
+                f,
+                "{} is negative, so it's named after {} with \"nega\" in front",
+                self.number, n
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(e: &Explanation) -> Vec<(i64, i64, usize)> {
+        e.candidates
+            .iter()
+            .map(|f| (f.smaller, f.larger, f.root_count))
+            .collect()
+    }
+
+    #[test]
+    fn tie_break() {
+        let mut cache = Cache::default();
+        let e = explain(646, &mut cache);
+        assert_eq!(pairs(&e), vec![(19, 34, 5), (17, 38, 5), (2, 323, 5)]);
+        assert_eq!(e.chosen, Some(0));
+        assert_eq!(e.reason, Reason::ClosestFactors);
+    }
+
+    #[test]
+    fn fewest_roots() {
+        let mut cache = Cache::default();
+        let e = explain(600, &mut cache);
+        assert_eq!(e.reason, Reason::FewestRoots);
+        let chosen = e.candidates[e.chosen.unwrap()];
+        assert_eq!(
+            (chosen.smaller, chosen.larger, chosen.root_count),
+            (6, 100, 2)
+        );
+        assert!(e.candidates.iter().all(|f| f.root_count >= 2));
+    }
+
+    #[test]
+    fn other_reasons() {
+        let mut cache = Cache::default();
+        assert_eq!(explain(0, &mut cache).reason, Reason::Special);
+        assert_eq!(explain(1, &mut cache).reason, Reason::Special);
+        assert_eq!(explain(36, &mut cache).reason, Reason::Root);
+        let e = explain(19, &mut cache);
+        assert_eq!(e.reason, Reason::Prime);
+        assert!(e.candidates.is_empty());
+        assert_eq!(e.chosen, None);
+    }
+
+    #[test]
+    fn negative() {
+        let mut cache = Cache::default();
+        let e = explain(-646, &mut cache);
+        assert!(e.negative);
+        assert_eq!(e.number, -646);
+        assert_eq!(pairs(&e), pairs(&explain(646, &mut cache)));
+        assert_eq!(e.chosen, Some(0));
+        assert_eq!(e.reason, Reason::ClosestFactors);
+        assert!(!explain(646, &mut cache).negative);
+        assert_eq!(explain(-5, &mut cache).reason, Reason::Root);
+        assert_eq!(explain(-1, &mut cache).reason, Reason::Special);
+        assert_eq!(explain(-19, &mut cache).reason, Reason::Prime);
+    }
+
+    #[test]
+    fn display() {
+        let mut cache = Cache::default();
+        assert_eq!(
+            explain(646, &mut cache).to_string(),
+            "646 = 19 * 34: 5 roots (chosen)\n\
+            646 = 17 * 38: 5 roots\n\
+            646 = 2 * 323: 5 roots\n\
+            several factor pairs need the fewest roots, \
+            so the one with the closest factors was chosen\n"
+        );
+        assert_eq!(
+            explain(-19, &mut cache).to_string(),
+            "19 is prime, so it's named after 18 with \"un\" in front\n\
+            -19 is negative, so it's named after 19 with \"nega\" in front\n"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use crate::integer::BaseInteger;
+use alloc::{vec, vec::Vec};
+
+// Bases for the Miller-Rabin test. The first 12 primes give correct results
+// for every n < 3.3 * 10^24, the rest make mistakes extremely unlikely for
+// larger numbers, but not impossible (see `unsigned_base_name`).
+const WITNESSES: [u64; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+// numbers below this are factored by trial division alone
+const TRIAL_DIVISION_LIMIT: u64 = 1000;
+
+fn is_zero<T: BaseInteger>(n: &T) -> bool {
+    n.to_u64() == Some(0)
+}
+
+fn is_one<T: BaseInteger>(n: &T) -> bool {
+    n.to_u64() == Some(1)
+}
+
+fn gcd<T: BaseInteger>(a: &T, b: &T) -> T {
+    let mut a = a.clone();
+    let mut b = b.clone();
+    while !is_zero(&b) {
+        let r = a.rem(&b);
+        a = b;
+        b = r;
+    }
+    a
+}
+
+fn pow_mod<T: BaseInteger>(base: &T, exp: &T, m: &T) -> T {
+    let mut res = T::from_u64(1);
+    for i in (0..exp.bits()).rev() {
+        res = res.mul_mod(&res, m);
+        if exp.bit(i) {
+            res = res.mul_mod(base, m);
+        }
+    }
+    res
+}
+
+// input: odd, > TRIAL_DIVISION_LIMIT
+fn is_prime<T: BaseInteger>(n: &T) -> bool {
+    let n_minus_one = n.minus_one();
+    let mut s = 0;
+    while !n_minus_one.bit(s) {
+        s += 1;
+    }
+    let d = n_minus_one.shr(s);
+    let witnesses = if n.bits() <= 80 {
+        &WITNESSES[..12]
+    } else {
+        &WITNESSES[..]
+    };
+    'witnesses: for &a in witnesses {
+        let mut x = pow_mod(&T::from_u64(a), &d, n);
+        if is_one(&x) || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.mul_mod(&x, n);
+            if x == n_minus_one {
+                continue 'witnesses;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+// Pollard's rho algorithm
+// input: odd, composite
+// output: a non-trivial factor of n
+fn find_factor<T: BaseInteger>(n: &T) -> T {
+    for c in 1.. {
+        let c = T::from_u64(c);
+        let f = |x: &T| x.mul_mod(x, n).add_mod(&c, n);
+        let mut x = T::from_u64(2);
+        let mut y = x.clone();
+        loop {
+            x = f(&x);
+            y = f(&f(&y));
+            let d = gcd(&x.abs_diff(&y), n);
+            if d == *n {
+                // the sequence cycled without finding a factor, try another c
+                break;
+            }
+            if !is_one(&d) {
+                return d;
+            }
+        }
+    }
+    unreachable!()
+}
+
+// Returns the prime factors of n with their exponents, in ascending order
+// input: >= 1
+pub(crate) fn prime_factors<T: BaseInteger>(n: &T) -> Vec<(T, u32)> {
+    let mut primes = vec![];
+    let mut n = n.clone();
+    for p in 2..TRIAL_DIVISION_LIMIT {
+        let p = T::from_u64(p);
+        if p.mul(&p) > n {
+            break;
+        }
+        while p.divides(&n) {
+            n = n.div_by(&p);
+            primes.push(p.clone());
+        }
+    }
+    let mut remaining = vec![n];
+    while let Some(n) = remaining.pop() {
+        if is_one(&n) {
+            continue;
+        }
+        if n.to_u64()
+            .is_some_and(|n| n < TRIAL_DIVISION_LIMIT * TRIAL_DIVISION_LIMIT)
+            || is_prime(&n)
+        {
+            primes.push(n);
+        } else {
+            let d = find_factor(&n);
+            remaining.push(n.div_by(&d));
+            remaining.push(d);
+        }
+    }
+    primes.sort();
+    let mut res: Vec<(T, u32)> = vec![];
+    for p in primes {
+        match res.last_mut() {
+            Some((q, k)) if *q == p => *k += 1,
+            _ => res.push((p, 1)),
+        }
+    }
+    res
+}
+
+// Returns every divisor d of n with 2 <= d <= sqrt(n), largest first
+// input: >= 1
+pub(crate) fn small_divisors<T: BaseInteger>(n: &T) -> Vec<T> {
+    small_divisors_from(n, &prime_factors(n))
+}
+
+// Like `small_divisors`, given the prime factors of n
+pub(crate) fn small_divisors_from<T: BaseInteger>(n: &T, primes: &[(T, u32)]) -> Vec<T> {
+    let mut divisors = vec![T::from_u64(1)];
+    for (p, k) in primes {
+        for i in 0..divisors.len() {
+            let mut d = divisors[i].clone();
+            for _ in 0..*k {
+                d = d.mul(p);
+                // d * d <= n, written so that it can't overflow
+                if d > n.div_by(&d) {
+                    break;
+                }
+                divisors.push(d.clone());
+            }
+        }
+    }
+    divisors.retain(|d| !is_one(d));
+    divisors.sort_by(|a, b| b.cmp(a));
+    divisors
+}
+
+// Every divisor of a number, indexed by their exponents: with prime factors
+// p0^k0 * p1^k1 * ..., the divisor p0^e0 * p1^e1 * ... is at index
+// e0 + e1 * (k0 + 1) + e2 * (k0 + 1) * (k1 + 1) + ...
+//
+// If a divides d, the index of d / a is the index of d minus that of a.
+pub(crate) struct Divisors<T> {
+    pub(crate) values: Vec<T>,
+    // the exponent and index stride of each prime
+    strides: Vec<(usize, usize)>,
+}
+
+impl<T: BaseInteger> Divisors<T> {
+    pub(crate) fn new(primes: &[(T, u32)]) -> Self {
+        let mut values = vec![T::from_u64(1)];
+        let mut strides = vec![];
+        for (p, k) in primes {
+            strides.push((*k as usize, values.len()));
+            let lower = values.len();
+            for i in lower..lower * (*k as usize + 1) {
+                let d = values[i - lower].mul(p);
+                values.push(d);
+            }
+        }
+        Self { values, strides }
+    }
+
+    // Calls `f` with the index of every divisor of the divisor at `index`,
+    // including 1 and itself
+    pub(crate) fn for_each_divisor(&self, index: usize, mut f: impl FnMut(usize)) {
+        let limits: Vec<(usize, usize)> = self
+            .strides
+            .iter()
+            .map(|&(k, stride)| (index / stride % (k + 1), stride))
+            .collect();
+        let mut exponents = vec![0; limits.len()];
+        let mut divisor = 0;
+        loop {
+            f(divisor);
+            // count up in mixed radix, with the limits as the digits
+            let mut i = 0;
+            loop {
+                let (limit, stride) = match limits.get(i) {
+                    Some(&limit) => limit,
+                    // every exponent is back at zero
+                    None => return,
+                };
+                if exponents[i] < limit {
+                    exponents[i] += 1;
+                    divisor += stride;
+                    break;
+                }
+                divisor -= exponents[i] * stride;
+                exponents[i] = 0;
+                i += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_prime_factors(mut n: u64) -> Vec<(u64, u32)> {
+        let mut res = vec![];
+        let mut p = 2;
+        while p * p <= n {
+            let mut k = 0;
+            while n % p == 0 {
+                n /= p;
+                k += 1;
+            }
+            if k > 0 {
+                res.push((p, k));
+            }
+            p += 1;
+        }
+        if n > 1 {
+            res.push((n, 1));
+        }
+        res
+    }
+
+    #[test]
+    fn small_numbers() {
+        for n in 1..5000u64 {
+            assert_eq!(prime_factors(&n), naive_prime_factors(n), "{}", n);
+            let expected: Vec<u64> = (2..n).rev().filter(|&d| d * d <= n && n % d == 0).collect();
+            assert_eq!(small_divisors(&n), expected, "{}", n);
+        }
+    }
+
+    #[test]
+    fn divisor_indices() {
+        for n in 1..500u64 {
+            let divisors = Divisors::new(&prime_factors(&n));
+            let mut values = divisors.values.clone();
+            values.sort();
+            let expected: Vec<u64> = (1..=n).filter(|&d| n % d == 0).collect();
+            assert_eq!(values, expected);
+            for (i, d) in divisors.values.iter().enumerate() {
+                let mut found = vec![];
+                divisors.for_each_divisor(i, |j| {
+                    assert_eq!(divisors.values[i - j] * divisors.values[j], *d);
+                    found.push(divisors.values[j]);
+                });
+                found.sort();
+                let expected: Vec<u64> = (1..=*d).filter(|&a| d % a == 0).collect();
+                assert_eq!(found, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn large_numbers() {
+        // a prime just below 2^63, and the product of two 9-digit primes
+        assert_eq!(prime_factors(&9_223_372_036_854_775_783u64).len(), 1);
+        assert_eq!(
+            prime_factors(&(999_999_937u64 * 999_999_929)),
+            vec![(999_999_929, 1), (999_999_937, 1)]
+        );
+        assert_eq!(prime_factors(&(1u64 << 63)), vec![(2, 63)]);
+        assert_eq!(prime_factors(&u64::MAX), naive_prime_factors(u64::MAX));
+        // 2^127 - 1 is prime
+        let mersenne = (1u128 << 127) - 1;
+        assert_eq!(prime_factors(&mersenne), vec![(mersenne, 1)]);
+        let p = u128::from(18_446_744_073_709_551_557u64);
+        assert_eq!(prime_factors(&(p * 3)), vec![(3, 1), (p, 1)]);
+    }
+}
@@ -0,0 +1,133 @@
+use core::{convert::TryFrom, fmt, hash::Hash};
+
+pub(crate) mod private {
+    pub trait Sealed {}
+}
+
+/// An unsigned integer type that can be used to name bases, see
+/// `unsigned_base_name`
+///
+/// This is implemented for `u64` and `u128`, as well as for
+/// `num_bigint::BigUint` if the `bigint` feature is enabled.
+pub trait BaseInteger: private::Sealed + Clone + Ord + Hash + fmt::Debug {
+    #[doc(hidden)]
+    fn from_u64(n: u64) -> Self;
+    #[doc(hidden)]
+    fn to_u64(&self) -> Option<u64>;
+    // whether `self` is a divisor of `n`
+    #[doc(hidden)]
+    fn divides(&self, n: &Self) -> bool;
+    #[doc(hidden)]
+    fn div_by(&self, divisor: &Self) -> Self;
+    // input: >= 1
+    #[doc(hidden)]
+    fn minus_one(&self) -> Self;
+    // only used where the product is known to fit
+    #[doc(hidden)]
+    fn mul(&self, other: &Self) -> Self;
+    #[doc(hidden)]
+    fn rem(&self, m: &Self) -> Self;
+    #[doc(hidden)]
+    fn abs_diff(&self, other: &Self) -> Self;
+    // inputs: < m
+    #[doc(hidden)]
+    fn add_mod(&self, other: &Self, m: &Self) -> Self;
+    // inputs: < m
+    #[doc(hidden)]
+    fn mul_mod(&self, other: &Self, m: &Self) -> Self;
+    // number of significant bits
+    #[doc(hidden)]
+    fn bits(&self) -> u64;
+    #[doc(hidden)]
+    fn bit(&self, i: u64) -> bool;
+    #[doc(hidden)]
+    fn shr(&self, k: u64) -> Self;
+}
+
+macro_rules! impl_base_integer {
+    ($t:ty, $mul_mod:expr) => {
+        impl private::Sealed for $t {}
+
+        impl BaseInteger for $t {
+            fn from_u64(n: u64) -> Self {
+                n.into()
+            }
+
+            fn to_u64(&self) -> Option<u64> {
+                u64::try_from(*self).ok()
+            }
+
+            fn divides(&self, n: &Self) -> bool {
+                n % self == 0
+            }
+
+            fn div_by(&self, divisor: &Self) -> Self {
+                self / divisor
+            }
+
+            fn minus_one(&self) -> Self {
+                self - 1
+            }
+
+            fn mul(&self, other: &Self) -> Self {
+                self * other
+            }
+
+            fn rem(&self, m: &Self) -> Self {
+                self % m
+            }
+
+            fn abs_diff(&self, other: &Self) -> Self {
+                <$t>::abs_diff(*self, *other)
+            }
+
+            fn add_mod(&self, other: &Self, m: &Self) -> Self {
+                // written so that it can't overflow
+                if *self >= m - other {
+                    self - (m - other)
+                } else {
+                    self + other
+                }
+            }
+
+            fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+                $mul_mod(*self, *other, *m)
+            }
+
+            fn bits(&self) -> u64 {
+                (<$t>::MAX.count_ones() - self.leading_zeros()).into()
+            }
+
+            fn bit(&self, i: u64) -> bool {
+                self >> i & 1 == 1
+            }
+
+            fn shr(&self, k: u64) -> Self {
+                self >> k
+            }
+        }
+    };
+}
+
+impl_base_integer!(u64, |a, b, m| {
+    (u128::from(a) * u128::from(b) % u128::from(m)) as u64
+});
+impl_base_integer!(u128, mul_mod_u128);
+
+fn mul_mod_u128(a: u128, b: u128, m: u128) -> u128 {
+    if let (Ok(a), Ok(b), Ok(m)) = (u64::try_from(a), u64::try_from(b), u64::try_from(m)) {
+        return u64::mul_mod(&a, &b, &m).into();
+    }
+    // there's no wider type to multiply in, so add and double instead
+    let mut res = 0;
+    let mut a = a;
+    let mut b = b;
+    while b != 0 {
+        if b & 1 == 1 {
+            res = res.add_mod(&a, &m);
+        }
+        a = a.add_mod(&a, &m);
+        b >>= 1;
+    }
+    res
+}
@@ -1,20 +1,92 @@
+// tests always use `std`
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(dead_code)]
 
+extern crate alloc;
+
+mod abbreviation;
+mod alternatives;
 mod base;
+#[cfg(feature = "bigint")]
+mod bigint;
+mod cache;
+mod explain;
+mod factor;
+mod integer;
+mod morpheme;
+mod parse;
+mod precomputed;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod sync_cache;
 
-use base::*;
+use alloc::string::String;
 
+pub use abbreviation::{
+    abbreviation_table, AbbreviationEntry, AbbreviationOptions, Collision, VowelPolicy,
+};
+pub use alternatives::{alternative_names, AlternativeName, AlternativeNames};
+pub use base::Base;
 pub use base::BaseName;
-pub use base::Cache;
+pub use base::BaseValue;
+pub use base::NameError;
+pub use base::Root;
+pub use cache::{Cache, CachePolicy, CacheStats};
+pub use explain::{explain, Explanation, Factorisation, Reason};
+pub use integer::BaseInteger;
+pub use morpheme::{Morpheme, MorphemeKind};
+pub use parse::ParseError;
+pub use precomputed::{precomputed_abbreviation, precomputed_limit};
+#[cfg(feature = "std")]
+pub use snapshot::SnapshotError;
+#[cfg(feature = "std")]
+pub use sync_cache::SyncCache;
 
 /// Get the name of a given number base
 pub fn base_name(number: i64, cache: &mut Cache) -> BaseName {
     BaseName(Base::new(number, cache), true)
 }
 
-/// Get the name of a given number base
+/// Get the name of a given non-negative number base, for bases that may not
+/// fit in an `i64`
+///
+/// This works for `u64` and `u128`, and for `num_bigint::BigUint` if the
+/// `bigint` feature is enabled. Names are chosen as in `base_name`.
+///
+/// Bases are factored with a primality test that is only proven correct
+/// below 2^80. Above that it's probabilistic: a composite factor that passes
+/// it would be named as a prime. This is extremely unlikely unless the number
+/// was constructed to fool the test.
+pub fn unsigned_base_name<T: BaseInteger>(number: T, cache: &mut Cache<T>) -> BaseName {
+    BaseName(Base::new_unsigned(number, cache), true)
+}
+
+/// Get the name of a fractional base, e.g. "votdecimal" for 1/10
+///
+/// # Panics
+///
+/// Panics if the numerator is zero (unless the denominator is 1), or if the
+/// denominator is zero. Use `try_rational_base_name` to handle these
+/// cases instead.
 pub fn rational_base_name(num: i64, den: i64, cache: &mut Cache) -> BaseName {
-    BaseName(Base::new_frac(num, den, cache), true)
+    match try_rational_base_name(num, den, cache) {
+        Ok(name) => name,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+/// Get the name of a fractional base, e.g. "votdecimal" for 1/10
+///
+/// A negative numerator is named by moving its sign to the denominator, so
+/// -2/3 is "bivotnegatrinary". Returns an error if the numerator is zero
+/// (unless the denominator is 1), or if the denominator is zero.
+pub fn try_rational_base_name(
+    num: i64,
+    den: i64,
+    cache: &mut Cache,
+) -> Result<BaseName, NameError> {
+    Ok(BaseName(Base::new_frac(num, den, cache)?, true))
 }
 
 /// Get the name of a non-rational base, e.g. base pi, phi or tau
@@ -26,13 +98,84 @@ pub fn non_rational_base_name(name: &str, greater_than_six: bool, one_syllable:
 }
 
 /// Get the abbreviation of the given number base (e.g. DEC for 10)
+///
+/// Base zero is abbreviated as NUL, and negative bases as the abbreviation
+/// of their absolute value with a minus sign (e.g. -BIN for negabinary).
+///
+/// Each abbreviation depends on the abbreviations of every smaller base, so
+/// those are calculated first (or taken from the precomputed tables and the
+/// cache). The time this takes grows faster than the base itself, to tens of
+/// seconds around 100000, so large bases are out of scope: bases near
+/// `i64::MAX` can't be abbreviated in practice.
 pub fn base_abbreviation(number: i64, cache: &mut Cache) -> String {
-    find_abbreviation(number, cache).to_string()
+    cache.integer_abbreviation(number)
+}
+
+/// Get the abbreviation of a fractional base, e.g. UNA/DEC for votdecimal
+///
+/// The numerator and denominator are abbreviated as in `base_abbreviation`.
+///
+/// # Panics
+///
+/// Panics in the same cases as `rational_base_name`. Use
+/// `try_rational_base_abbreviation` to handle these cases instead.
+pub fn rational_base_abbreviation(num: i64, den: i64, cache: &mut Cache) -> String {
+    match try_rational_base_abbreviation(num, den, cache) {
+        Ok(abbr) => abbr,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+/// Get the abbreviation of a fractional base, e.g. UNA/DEC for votdecimal
+///
+/// Returns an error in the same cases as `try_rational_base_name`.
+pub fn try_rational_base_abbreviation(
+    num: i64,
+    den: i64,
+    cache: &mut Cache,
+) -> Result<String, NameError> {
+    cache.rational_abbreviation(num, den)
+}
+
+/// Get the abbreviation of a non-rational base, e.g. ~PHI for phinary
+///
+/// The tilde makes sure that these never collide with the abbreviations of
+/// integer or fractional bases. The whole custom name is always used, so
+/// `AbbreviationOptions` don't apply, and no cache is needed.
+pub fn non_rational_base_abbreviation(name: &str) -> String {
+    base::non_rational_abbreviation(name)
+}
+
+/// Get the number base with the given abbreviation (e.g. 10 for DEC), or
+/// `None` if no base up to `limit` has it
+///
+/// Abbreviations are case-sensitive, and negative bases are found up to
+/// `-limit`. Finding a base means calculating every
+/// abbreviation up to it, so `limit` bounds the amount of work for
+/// abbreviations that don't exist. Results are remembered in the cache, so
+/// later lookups of bases up to the same limit are fast.
+pub fn base_from_abbreviation(abbr: &str, limit: i64, cache: &mut Cache) -> Option<i64> {
+    cache.base_from_abbreviation(abbr, limit)
+}
+
+/// Parse a given base name (e.g. "seximal" or "tetroctal") into a number
+///
+/// Names are accepted both with and without merged vowels, so "tetroctal"
+/// and "tetraoctal" both parse as 32. Names that `base_name` would never
+/// produce, like "bibinary" for 4, are rejected with
+/// `ParseError::NonCanonical`.
+pub fn parse_base_name(name: &str) -> Result<i64, ParseError> {
+    parse::parse_integer(name)
 }
 
-/// Parse a given base name into a number
-fn parse_base_name(name: &str) -> Option<i64> {
-    Some(Base::try_parse(name)?.to_number())
+/// Parse a given base name into its structure, e.g. to get the value of
+/// fractional bases like "votdecimal"
+///
+/// The result is the same tree that the naming functions build, so
+/// `parse_base(&base_name(n, cache).to_string())` equals
+/// `*base_name(n, cache).base()`.
+pub fn parse_base(name: &str) -> Result<Base, ParseError> {
+    parse::parse_base(name)
 }
 
 #[cfg(test)]
@@ -48,7 +191,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore]
     fn roundtrip() {
         let mut cache = Cache::default();
         for n in 0..1000 {
@@ -59,6 +201,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn abbreviation_lookup() {
+        let mut cache = Cache::default();
+        assert_eq!(base_from_abbreviation("BAKR", 1000, &mut cache), Some(585));
+        assert_eq!(base_from_abbreviation("SEX", 1000, &mut cache), Some(6));
+        assert_eq!(base_from_abbreviation("DOZ", 1000, &mut cache), Some(12));
+        assert_eq!(base_from_abbreviation("HSS", 1000, &mut cache), Some(841));
+        assert_eq!(base_from_abbreviation("HSS", 840, &mut cache), None);
+        assert_eq!(base_from_abbreviation("doz", 1000, &mut cache), None);
+        assert_eq!(base_from_abbreviation("DEC", -1, &mut cache), None);
+        for n in 1..3000 {
+            let abbr = base_abbreviation(n, &mut cache);
+            assert_eq!(base_from_abbreviation(&abbr, 3000, &mut cache), Some(n));
+        }
+
+        // a fresh cache only calculates abbreviations up to the one it finds
+        let mut cache = Cache::default();
+        assert_eq!(base_from_abbreviation("DEC", 3000, &mut cache), Some(10));
+        assert!(cache.stats().abbreviation_entries <= 11);
+        assert_eq!(base_from_abbreviation("XYZ", 100, &mut cache), None);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse_base_name("votdecimal"),
+            Err(ParseError::Unrepresentable)
+        );
+        assert_eq!(
+            parse_base_name("tetrseximal").unwrap_err().to_string(),
+            "unrecognised \"seximal\" at byte 4, expected one of \"octo\", \"enna\", \"icosi\", \"octal\", \"elevenary\", \"un\""
+        );
+    }
+
     #[test]
     fn factorised_names() {
         let mut cache = Cache::default();
@@ -100,6 +276,218 @@ mod tests {
         );
     }
 
+    #[test]
+    fn name_structure() {
+        let mut cache = Cache::default();
+        let name = base_name(646, &mut cache);
+        let (a, b) = name.base().factors().unwrap();
+        assert_eq!(
+            b.factors(),
+            Some((&Base::Root(Root::Binary), &Base::Root(Root::Suboptimal)))
+        );
+        let one_below = a.one_below().unwrap();
+        assert_eq!(
+            one_below.factors(),
+            Some((&Base::Root(Root::Trinary), &Base::Root(Root::Seximal)))
+        );
+
+        let name = rational_base_name(1, -10, &mut cache);
+        let (num, den) = name.base().fraction().unwrap();
+        assert_eq!(num, &Base::Unary);
+        let den = den.negated().unwrap();
+        assert_eq!(den.root().map(Root::to_number), Some(10));
+
+        let name = non_rational_base_name("phi", false, true);
+        assert_eq!(name.into_base().custom_name(), Some("phi"));
+    }
+
+    #[test]
+    fn undefined_inputs() {
+        let mut cache = Cache::default();
+        assert_eq!(
+            try_rational_base_name(0, 5, &mut cache).err(),
+            Some(NameError::InvalidNumerator)
+        );
+        assert_eq!(
+            try_rational_base_name(-2, 3, &mut cache)
+                .unwrap()
+                .to_string(),
+            "bivotnegatrinary"
+        );
+        assert_eq!(
+            try_rational_base_name(-2, -3, &mut cache)
+                .unwrap()
+                .to_string(),
+            "bivottrinary"
+        );
+        assert_eq!(
+            try_rational_base_name(-2, -1, &mut cache)
+                .unwrap()
+                .to_string(),
+            "binary"
+        );
+        assert_eq!(
+            try_rational_base_name(2, 0, &mut cache).err(),
+            Some(NameError::ZeroDenominator)
+        );
+        assert_eq!(
+            try_rational_base_name(-2, 1, &mut cache)
+                .unwrap()
+                .to_string(),
+            "negabinary"
+        );
+        assert_eq!(base_abbreviation(0, &mut cache), "NUL");
+        assert_eq!(base_abbreviation(6, &mut cache), "SEX");
+        assert_eq!(
+            try_rational_base_abbreviation(0, 5, &mut cache),
+            Err(NameError::InvalidNumerator)
+        );
+        assert_eq!(
+            try_rational_base_abbreviation(2, 0, &mut cache),
+            Err(NameError::ZeroDenominator)
+        );
+        assert_eq!(
+            try_rational_base_abbreviation(-2, 3, &mut cache),
+            try_rational_base_abbreviation(2, -3, &mut cache)
+        );
+        assert_eq!(
+            try_rational_base_abbreviation(-2, -3, &mut cache).unwrap(),
+            "BIN/TRI"
+        );
+    }
+
+    #[test]
+    fn other_abbreviations() {
+        let mut cache = Cache::default();
+        assert_eq!(base_abbreviation(-2, &mut cache), "-BIN");
+        assert_eq!(base_abbreviation(-585, &mut cache), "-BAKR");
+        assert_eq!(base_abbreviation(-1, &mut cache), "-UNA");
+        assert_eq!(rational_base_abbreviation(1, 10, &mut cache), "UNA/DEC");
+        assert_eq!(rational_base_abbreviation(2, 3, &mut cache), "BIN/TRI");
+        assert_eq!(rational_base_abbreviation(1, -2, &mut cache), "UNA/-BIN");
+        assert_eq!(rational_base_abbreviation(-6, 1, &mut cache), "-SEX");
+        assert_eq!(non_rational_base_abbreviation("phi"), "~PHI");
+        assert_eq!(non_rational_base_abbreviation("tau"), "~TAU");
+
+        assert_eq!(base_from_abbreviation("NUL", 100, &mut cache), Some(0));
+        assert_eq!(base_from_abbreviation("-BIN", 100, &mut cache), Some(-2));
+        assert_eq!(base_from_abbreviation("-NUL", 100, &mut cache), None);
+        assert_eq!(base_from_abbreviation("--BIN", 100, &mut cache), None);
+        assert_eq!(base_from_abbreviation("-HSS", 840, &mut cache), None);
+        for n in -500..500 {
+            let abbr = base_abbreviation(n, &mut cache);
+            assert_eq!(base_from_abbreviation(&abbr, 500, &mut cache), Some(n));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "denominator must not be zero")]
+    fn rational_base_abbreviation_panics() {
+        let mut cache = Cache::default();
+        rational_base_abbreviation(1, 0, &mut cache);
+    }
+
+    #[test]
+    #[should_panic(expected = "numerator must not be zero")]
+    fn rational_base_name_panics() {
+        let mut cache = Cache::default();
+        rational_base_name(0, 5, &mut cache);
+    }
+
+    #[test]
+    fn extreme_names() {
+        let mut cache = Cache::default();
+        let max = "heptaheptahenoctennasnahenbiheptafetahenhenbilevasnaheptoctosnasna\
+            henennabiheptasnahentessertriheptasnunheptafetatesserunbielevenary";
+        let min = "negoctotessertessertessertessertessertessertessertessertessertesser\
+            tessertessertessertesserhex";
+        check_name(i64::MAX, max, &mut cache);
+        check_name(i64::MIN + 1, &format!("nega{}", max), &mut cache);
+        check_name(i64::MIN, min, &mut cache);
+        assert_eq!(parse_base_name(max), Ok(i64::MAX));
+        assert_eq!(parse_base_name(min), Ok(i64::MIN));
+    }
+
+    #[test]
+    fn extreme_values() {
+        let mut cache = Cache::default();
+        let e = explain(i64::MIN, &mut cache);
+        assert!(e.negative);
+        assert!(e.chosen.is_some());
+        // 2^63 doesn't fit in an i64, but its negative does
+        let pow_63 = "octotessertessertessertessertessertessertesser\
+            tessertessertessertessertessertessertesserhex";
+        assert_eq!(parse_base_name(pow_63), Err(ParseError::Unrepresentable));
+        let min = Base::Nega(Box::new(parse_base(pow_63).unwrap()));
+        assert_eq!(min.value(), Some(BaseValue::Integer(i64::MIN)));
+        // too large to check, but that doesn't matter if it's not an i64
+        let pow_97 = "octo".repeat(32) + "binary";
+        assert_eq!(parse_base(&pow_97), Err(ParseError::TooLarge));
+        assert_eq!(parse_base_name(&pow_97), Err(ParseError::Unrepresentable));
+    }
+
+    #[test]
+    fn unsigned_names() {
+        let mut cache = Cache::default();
+        let mut cache_128 = Cache::default();
+        for n in 0..2000 {
+            let name = base_name(n, &mut cache).to_string();
+            assert_eq!(unsigned_base_name(n as u64, &mut cache).to_string(), name);
+            assert_eq!(
+                unsigned_base_name(n as u128, &mut cache_128).to_string(),
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn large_unsigned_names() {
+        let mut cache = Cache::default();
+        assert_eq!(
+            unsigned_base_name(10u128.pow(30), &mut cache).to_string(),
+            "hecto".repeat(14) + "centesimal"
+        );
+        // 2^64 + 1 = 274177 * 67280421310721
+        assert_eq!(
+            unsigned_base_name((1u128 << 64) + 1, &mut cache).to_string(),
+            "henmaltetraheptatesserfetasnunicosihenbihenbilevasnasnatesserhendoza\
+            henpentahexasnasnatetruntrihexundecunhexoctunbintetrunhexaseptimal"
+        );
+        let max = i64::MAX as u128;
+        assert_eq!(
+            unsigned_base_name(max, &mut cache).to_string(),
+            base_name(i64::MAX, &mut Cache::default()).to_string()
+        );
+    }
+
+    #[test]
+    fn fifteen_digit_names() {
+        let mut cache = Cache::default();
+        for n in 999_999_999_999_000..999_999_999_999_010 {
+            let name = base_name(n, &mut cache).to_string();
+            assert_eq!(parse_base_name(&name), Ok(n));
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn bigint_names() {
+        use num_bigint::BigUint;
+
+        let mut cache = Cache::default();
+        let mut big_cache = Cache::default();
+        for n in 0..2000u32 {
+            assert_eq!(
+                unsigned_base_name(BigUint::from(n), &mut big_cache).to_string(),
+                base_name(n.into(), &mut cache).to_string()
+            );
+        }
+        assert_eq!(
+            unsigned_base_name(BigUint::from(100u32).pow(20), &mut big_cache).to_string(),
+            "hecto".repeat(19) + "centesimal"
+        );
+    }
+
     #[test]
     fn custom_names() {
         assert_eq!(
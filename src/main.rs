@@ -1,15 +1,12 @@
-#[allow(dead_code)]
-mod base;
-
-use base::*;
+use bases::{abbreviation_table, base_abbreviation, base_name, explain, Cache};
 use std::{env, error};
 
 fn loop_all_numbers() -> ! {
     let mut n = 1;
     let mut cache = Cache::default();
     loop {
-        let name = BaseName(Base::new(n, &mut cache), true);
-        let abbr = find_abbreviation(n, &mut cache);
+        let name = base_name(n, &mut cache);
+        let abbr = base_abbreviation(n, &mut cache);
         println!("{}: {} ({})", n, name, abbr);
         n += 1;
     }
@@ -18,19 +15,57 @@ fn loop_all_numbers() -> ! {
 fn parse_and_display_number(n: &str) -> Result<(), Box<dyn error::Error>> {
     let n: i64 = n.parse()?;
     let mut cache = Cache::default();
-    let name = BaseName(Base::new(n, &mut cache), true);
+    let name = base_name(n, &mut cache);
+    println!("{}", name);
+    Ok(())
+}
+
+fn parse_and_explain_number(n: &str) -> Result<(), Box<dyn error::Error>> {
+    let n: i64 = n.parse()?;
+    let mut cache = Cache::default();
+    let name = base_name(n, &mut cache);
     println!("{}", name);
+    print!("{}", explain(n, &mut cache));
+    Ok(())
+}
+
+fn print_abbreviation_table(limit: &str) -> Result<(), Box<dyn error::Error>> {
+    let limit: i64 = limit.parse()?;
+    let mut cache = Cache::default();
+    for entry in abbreviation_table(limit, &mut cache) {
+        print!("{}: {}", entry.base, entry.abbreviation);
+        for (i, collision) in entry.collisions.iter().enumerate() {
+            let sep = if i == 0 { " (rejected " } else { ", " };
+            match collision.owner {
+                Some(owner) => print!("{}{} used by {}", sep, collision.candidate, owner),
+                None => print!("{}{} reserved", sep, collision.candidate),
+            }
+        }
+        if entry.collisions.is_empty() {
+            println!();
+        } else {
+            println!(")");
+        }
+    }
     Ok(())
 }
 
 fn main() {
     let args: Vec<_> = env::args().collect();
-    if args.len() <= 1 {
-        loop_all_numbers();
-    } else {
-        match parse_and_display_number(&args[1]) {
-            Ok(()) => (),
-            Err(e) => eprintln!("Error: {}", e),
-        }
+    let res = match &args[1..] {
+        [] => loop_all_numbers(),
+        [flag, n] if flag == "--explain" => parse_and_explain_number(n),
+        [flag, n] if flag == "--abbreviations" => print_abbreviation_table(n),
+        [n] if !n.starts_with("--") => parse_and_display_number(n),
+        _ => Err(concat!(
+            "usage: bases [<number>]\n",
+            "       bases --explain <number>\n",
+            "       bases --abbreviations <limit>"
+        )
+        .into()),
+    };
+    match res {
+        Ok(()) => (),
+        Err(e) => eprintln!("Error: {}", e),
     }
 }
@@ -0,0 +1,275 @@
+use crate::base::{Base, BaseName, BaseValue};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+/// The kind of a morpheme in a base name
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MorphemeKind {
+    /// A root (or "nullary"/"unary") on its own, e.g. "seximal" in "unseximal"
+    Root,
+    /// A root used as a prefix, e.g. "hexa" in "hexagesimal"
+    Prefix,
+    /// A root used after a prefix, e.g. "gesimal" in "hexagesimal", or the
+    /// ending of a non-rational base name, e.g. "nary" in "phinary"
+    Suffix,
+    /// "un", which adds one to the following name
+    Un,
+    /// "hen", which starts a prime used as a prefix
+    Hen,
+    /// "sna", which ends a prime used as a prefix
+    Sna,
+    /// "nega", which negates the following name
+    Nega,
+    /// "vot", which separates a numerator from the following denominator
+    Vot,
+    /// The custom part of a non-rational base name, e.g. "phi" in "phinary"
+    Custom,
+}
+
+/// A single morpheme of a base name
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Morpheme {
+    /// What kind of morpheme this is
+    pub kind: MorphemeKind,
+    /// The number this morpheme stands for. For roots, prefixes and suffixes
+    /// this is the root number. For "un", "hen", "sna" and "nega" it's the
+    /// value of the whole term they're part of (e.g. 19 for each of "hen" and
+    /// "sna" in "hentrihexasna"). "vot" and non-rational morphemes have no value.
+    pub value: Option<i64>,
+    /// Byte range of this morpheme in the displayed name
+    pub span: Range<usize>,
+}
+
+struct Segments(Vec<(MorphemeKind, Option<i64>, String)>);
+
+impl Segments {
+    fn push(&mut self, kind: MorphemeKind, value: Option<i64>, s: &str) {
+        self.0.push((kind, value, s.to_string()));
+    }
+
+    // mirrors `Base::prefix_name`
+    fn prefix_name(&mut self, base: &Base) {
+        match base {
+            Base::Root(r) => self.push(
+                MorphemeKind::Prefix,
+                Some(r.to_number().into()),
+                r.prefix_name(),
+            ),
+            Base::Prime(one_below) => {
+                let value = base.to_integer();
+                self.push(MorphemeKind::Hen, value, "hen");
+                self.prefix_name(one_below);
+                self.push(MorphemeKind::Sna, value, "sna");
+            }
+            Base::FactorPair(a, b) => {
+                self.prefix_name(a);
+                self.prefix_name(b);
+            }
+            // can't happen for bases created by this crate
+            _ => (),
+        }
+    }
+
+    // mirrors `Base::suffix_name`
+    fn suffix_name(&mut self, base: &Base) {
+        match base {
+            Base::Root(r) => self.push(
+                MorphemeKind::Suffix,
+                Some(r.to_number().into()),
+                r.suffix_name(),
+            ),
+            _ => self.format_name(base),
+        }
+    }
+
+    // mirrors `Base::format_name`
+    fn format_name(&mut self, base: &Base) {
+        match base {
+            Base::Nullary => self.push(MorphemeKind::Root, Some(0), "nullary"),
+            Base::Unary => self.push(MorphemeKind::Root, Some(1), "unary"),
+            Base::Root(r) => self.push(MorphemeKind::Root, Some(r.to_number().into()), r.name()),
+            Base::FactorPair(a, b) => {
+                self.prefix_name(a);
+                self.suffix_name(b);
+            }
+            Base::Prime(one_below) => {
+                self.push(MorphemeKind::Un, base.to_integer(), "un");
+                self.format_name(one_below);
+            }
+            Base::Nega(n) => {
+                self.push(MorphemeKind::Nega, base.to_integer(), "nega");
+                self.format_name(n);
+            }
+            Base::Vot(a, b) => {
+                if a.value() != Some(BaseValue::Integer(1)) {
+                    self.prefix_name(a);
+                }
+                self.push(MorphemeKind::Vot, None, "vot");
+                self.format_name(b);
+            }
+            Base::CustomLessThanSix(s) => {
+                self.push(MorphemeKind::Custom, None, s);
+                if s.ends_with(crate::base::is_vowel_or_y) {
+                    self.push(MorphemeKind::Suffix, None, "nary");
+                } else {
+                    self.push(MorphemeKind::Suffix, None, "ary");
+                }
+            }
+            Base::Imal(s) => {
+                self.push(MorphemeKind::Custom, None, s);
+                self.push(MorphemeKind::Suffix, None, "imal");
+            }
+            Base::Al(s) => {
+                self.push(MorphemeKind::Custom, None, s);
+                self.push(MorphemeKind::Suffix, None, "al");
+            }
+        }
+    }
+}
+
+impl BaseName {
+    /// Splits this name into its morphemes, in order. The spans refer to the
+    /// name as displayed, i.e. after vowels have been merged, so in
+    /// "tetroctal" the spans are "tetr" and "octal".
+    pub fn morphemes(&self) -> Vec<Morpheme> {
+        let mut segments = Segments(Vec::new());
+        segments.format_name(&self.0);
+        let segments = segments.0;
+
+        let mut res = Vec::with_capacity(segments.len());
+        let mut pos = 0;
+        for (i, (kind, value, s)) in segments.iter().enumerate() {
+            let mut start = 0;
+            let mut end = s.len();
+            // these are the same rules as in `FixupVowels`
+            if self.1 {
+                let prev = i.checked_sub(1).and_then(|i| segments[i].2.chars().last());
+                let next = segments.get(i + 1).and_then(|s| s.2.chars().next());
+                if prev == Some('i') && s.starts_with(['i', 'u']) {
+                    start = 1;
+                }
+                if s.ends_with(['a', 'o']) && matches!(next, Some('o' | 'e' | 'i' | 'u')) {
+                    end -= 1;
+                }
+            }
+            res.push(Morpheme {
+                kind: *kind,
+                value: *value,
+                span: pos..pos + end - start,
+            });
+            pos += end - start;
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::Cache;
+    use MorphemeKind::*;
+
+    fn segment(name: &BaseName) -> Vec<(MorphemeKind, Option<i64>, String)> {
+        let s = name.to_string();
+        name.morphemes()
+            .into_iter()
+            .map(|m| (m.kind, m.value, s[m.span].to_string()))
+            .collect()
+    }
+
+    #[track_caller]
+    fn check_morphemes(n: i64, expected: &[(MorphemeKind, Option<i64>, &str)]) {
+        let mut cache = Cache::default();
+        let name = BaseName(Base::new(n, &mut cache), true);
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(k, v, s)| (*k, *v, s.to_string()))
+            .collect();
+        assert_eq!(segment(&name), expected);
+    }
+
+    #[test]
+    fn simple_names() {
+        check_morphemes(10, &[(Root, Some(10), "decimal")]);
+        check_morphemes(0, &[(Root, Some(0), "nullary")]);
+        check_morphemes(
+            60,
+            &[(Prefix, Some(6), "hexa"), (Suffix, Some(10), "gesimal")],
+        );
+        check_morphemes(-2, &[(Nega, Some(-2), "nega"), (Root, Some(2), "binary")]);
+    }
+
+    #[test]
+    fn prime_names() {
+        check_morphemes(
+            646,
+            &[
+                (Hen, Some(19), "hen"),
+                (Prefix, Some(3), "tri"),
+                (Prefix, Some(6), "hexa"),
+                (Sna, Some(19), "sna"),
+                (Prefix, Some(2), "bi"),
+                (Suffix, Some(17), "suboptimal"),
+            ],
+        );
+        check_morphemes(
+            19,
+            &[
+                (Un, Some(19), "un"),
+                (Prefix, Some(3), "tri"),
+                (Suffix, Some(6), "seximal"),
+            ],
+        );
+    }
+
+    #[test]
+    fn merged_vowels() {
+        check_morphemes(32, &[(Prefix, Some(4), "tetr"), (Suffix, Some(8), "octal")]);
+        check_morphemes(
+            20 * 20 * 401,
+            &[
+                (Prefix, Some(20), "icosi"),
+                (Prefix, Some(20), "cosi"),
+                (Un, Some(401), "n"),
+                (Prefix, Some(20), "icosi"),
+                (Suffix, Some(20), "vigesimal"),
+            ],
+        );
+    }
+
+    #[test]
+    fn fractions_and_custom_names() {
+        let mut cache = Cache::default();
+        let name = BaseName(Base::new_frac(2, 3, &mut cache).unwrap(), true);
+        assert_eq!(
+            segment(&name),
+            vec![
+                (Prefix, Some(2), "bi".to_string()),
+                (Vot, None, "vot".to_string()),
+                (Root, Some(3), "trinary".to_string()),
+            ]
+        );
+        let name = BaseName(Base::new_custom("tau", true, true), false);
+        assert_eq!(
+            segment(&name),
+            vec![
+                (Custom, None, "tau".to_string()),
+                (Suffix, None, "imal".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn spans_cover_name() {
+        let mut cache = Cache::default();
+        for n in -100..2000 {
+            let name = BaseName(Base::new(n, &mut cache), true);
+            let s = name.to_string();
+            let joined: String = name.morphemes().into_iter().map(|m| &s[m.span]).collect();
+            assert_eq!(joined, s);
+        }
+    }
+}
@@ -0,0 +1,846 @@
+use crate::{
+    base::{Base, Root},
+    cache::{Cache, Map},
+};
+use alloc::{
+    boxed::Box,
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{convert::TryFrom, error, fmt};
+
+/// Error returned when a base name can't be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The name couldn't be split into morphemes
+    InvalidName {
+        /// Byte offset of the first morpheme that couldn't be recognised
+        offset: usize,
+        /// The unrecognised morpheme at `offset`: either a morpheme that
+        /// isn't valid there, or the text up to the next recognisable one
+        found: String,
+        /// Morphemes that would have been valid at `offset` (empty if the
+        /// name should have ended there)
+        expected: Vec<&'static str>,
+    },
+    /// The name is valid, but doesn't describe an integer that fits in an `i64`
+    Unrepresentable,
+    /// The name is made of valid morphemes, but isn't the name this crate
+    /// gives its base, e.g. "bibinary" (which is "quaternary") or "unoctal"
+    /// (which is "nonary")
+    NonCanonical,
+    /// The name describes a base too large to check that it's the canonical
+    /// name (one that doesn't fit in a `u64`)
+    TooLarge,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName {
+                offset,
+                found,
+                expected,
+            } => {
+                if found.is_empty() {
+                    write!(f, "unexpected end of name")?;
+                } else {
+                    write!(f, "unrecognised \"{}\" at byte {}", found, offset)?;
+                }
+                for (i, morpheme) in expected.iter().enumerate() {
+                    if i == 0 {
+                        write!(f, ", expected one of ")?;
+                    } else {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{}\"", morpheme)?;
+                }
+                Ok(())
+            }
+            Self::Unrepresentable => write!(f, "base is not an integer that fits in an i64"),
+            Self::NonCanonical => write!(f, "not the canonical name of its base"),
+            Self::TooLarge => write!(f, "base is too large to check"),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// A position in the input string
+///
+/// `FixupVowels` can remove the last letter of a morpheme ("tetra" + "octal"
+/// becomes "tetroctal") or the first letter of the following one ("icosi" +
+/// "icosi" becomes "icosicosi"), so we also need to keep track of how the
+/// previous morpheme ended.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Cursor {
+    pos: usize,
+    // last letter of the previous morpheme, before any vowels were removed
+    prev: Option<u8>,
+    // whether `prev` was removed by `FixupVowels`
+    elided: bool,
+}
+
+impl Cursor {
+    fn start() -> Self {
+        Self {
+            pos: 0,
+            prev: None,
+            elided: false,
+        }
+    }
+}
+
+fn starts_with_merging_vowel(s: &str) -> bool {
+    matches!(s.as_bytes()[0], b'o' | b'e' | b'i' | b'u')
+}
+
+// The value of a parse. Fractions are kept as written, because the name of
+// a fractional base depends on both parts, and anything that can't be part
+// of a canonical name (like "un" followed by a fraction) is `Other`.
+// Integers that don't fit in an `i128` are `None`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Value {
+    Integer(Option<i128>),
+    Vot(Option<i128>, Option<i128>),
+    Other,
+}
+
+impl Value {
+    fn map(self, f: impl FnOnce(i128) -> Option<i128>) -> Self {
+        match self {
+            Self::Integer(n) => Self::Integer(n.and_then(f)),
+            _ => Self::Other,
+        }
+    }
+
+    fn factor_pair(a: Self, b: Self) -> Self {
+        match (a, b) {
+            (Self::Integer(a), Self::Integer(b)) => {
+                Self::Integer(a.zip(b).and_then(|(a, b)| a.checked_mul(b)))
+            }
+            _ => Self::Other,
+        }
+    }
+
+    // a prefix followed by the rest of a name
+    fn prefix(a: Self, rest: Self) -> Self {
+        match (a, rest) {
+            (Self::Integer(a), Self::Vot(num, den)) => {
+                Self::Vot(a.zip(num).and_then(|(a, num)| a.checked_mul(num)), den)
+            }
+            (a, rest) => Self::factor_pair(a, rest),
+        }
+    }
+
+    fn to_i64(self) -> Option<i64> {
+        let n = match self {
+            Self::Integer(n) => n?,
+            Self::Vot(num, den) => {
+                let (num, den) = (num?, den?);
+                if num.checked_rem(den)? != 0 {
+                    return None;
+                }
+                num.checked_div(den)?
+            }
+            Self::Other => return None,
+        };
+        i64::try_from(n).ok()
+    }
+
+    fn vot(num: Self, den: Self) -> Self {
+        match (num, den) {
+            (Self::Integer(num), Self::Integer(den)) => Self::Vot(num, den),
+            _ => Self::Other,
+        }
+    }
+}
+
+fn root_value(root: Root) -> Value {
+    Value::Integer(Some(root.to_number().into()))
+}
+
+// Every way to parse part of the input, with the position after it. Parses
+// with the same value and position are merged.
+type Parses = Vec<(Value, Cursor)>;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Rule {
+    Name,
+    Factors,
+    Prefix,
+    Suffix,
+    HenFactors,
+}
+
+// Morphemes other than the names of roots
+const OTHER_MORPHEMES: [&str; 7] = ["nullary", "unary", "nega", "un", "vot", "hen", "sna"];
+
+fn morphemes() -> impl Iterator<Item = &'static str> {
+    let roots = Root::ALL
+        .iter()
+        .flat_map(|root| vec![root.name(), root.prefix_name(), root.suffix_name()]);
+    OTHER_MORPHEMES.iter().copied().chain(roots)
+}
+
+// Length of the longest morpheme that `s` starts with, also counting the
+// spellings without a final vowel
+fn morpheme_len(s: &str) -> Option<usize> {
+    morphemes()
+        .filter_map(|morpheme| {
+            if s.starts_with(morpheme) {
+                Some(morpheme.len())
+            } else if morpheme.ends_with(['a', 'o'])
+                && s.starts_with(&morpheme[..morpheme.len() - 1])
+            {
+                Some(morpheme.len() - 1)
+            } else {
+                None
+            }
+        })
+        .max()
+}
+
+// The unrecognised token at the start of `s`
+fn unmatched_token(s: &str) -> &str {
+    if let Some(len) = morpheme_len(s) {
+        return &s[..len];
+    }
+    let end = s
+        .char_indices()
+        .skip(1)
+        .map(|(i, _)| i)
+        .find(|&i| morpheme_len(&s[i..]).is_some())
+        .unwrap_or(s.len());
+    &s[..end]
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    // furthest position at which a morpheme failed to match
+    furthest: usize,
+    // morphemes that failed to match at `furthest`
+    expected: Vec<&'static str>,
+    // the parses of each rule at each position, so that the time it takes
+    // stays polynomial in the length of the input, with the failures
+    // recorded while finding them
+    memo: Map<(Rule, Cursor), (Parses, usize, Vec<&'static str>)>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            furthest: 0,
+            expected: Vec::new(),
+            memo: Map::new(),
+        }
+    }
+
+    // Parses the rules at every position a sequence of morphemes can reach,
+    // starting from the end, so that parsing the whole input only recurses as
+    // deep as a single morpheme rather than once per morpheme. Failures are
+    // only recorded once a rule is used by the actual parse.
+    fn prime(&mut self) {
+        let mut queue = BTreeSet::new();
+        queue.insert(Cursor::start());
+        let mut reachable = Vec::new();
+        while let Some(c) = queue.pop_first() {
+            for morpheme in morphemes() {
+                queue.extend(self.advance(c, morpheme));
+            }
+            reachable.push(c);
+        }
+        for &c in reachable.iter().rev() {
+            self.prefix(c);
+            self.suffix(c);
+            self.hen_factors(c);
+            self.factors(c);
+            self.name(c);
+        }
+        self.furthest = 0;
+        self.expected.clear();
+    }
+
+    fn memoized(
+        &mut self,
+        rule: Rule,
+        c: Cursor,
+        parse: fn(&mut Self, Cursor) -> Parses,
+    ) -> Parses {
+        if let Some((res, furthest, expected)) = self.memo.get(&(rule, c)) {
+            let (res, furthest, expected) = (res.clone(), *furthest, expected.clone());
+            self.merge_failures(furthest, &expected);
+            return res;
+        }
+        let outer_furthest = core::mem::replace(&mut self.furthest, 0);
+        let outer_expected = core::mem::take(&mut self.expected);
+        let mut res = parse(self, c);
+        res.sort_unstable();
+        res.dedup();
+        let furthest = core::mem::replace(&mut self.furthest, outer_furthest);
+        let expected = core::mem::replace(&mut self.expected, outer_expected);
+        self.merge_failures(furthest, &expected);
+        self.memo
+            .insert((rule, c), (res.clone(), furthest, expected));
+        res
+    }
+
+    fn merge_failures(&mut self, furthest: usize, expected: &[&'static str]) {
+        self.record_failure(furthest, None);
+        for &morpheme in expected {
+            self.record_failure(furthest, Some(morpheme));
+        }
+    }
+
+    fn record_failure(&mut self, pos: usize, morpheme: Option<&'static str>) {
+        if pos > self.furthest {
+            self.furthest = pos;
+            self.expected.clear();
+        }
+        if pos == self.furthest {
+            if let Some(morpheme) = morpheme {
+                if !self.expected.contains(&morpheme) {
+                    self.expected.push(morpheme);
+                }
+            }
+        }
+    }
+
+    // Returns all positions after matching `morpheme` at `c`. This accepts
+    // both the spelling produced by `FixupVowels` and the original spelling,
+    // so "tetr" + "octal" matches as well as "tetra" + "octal".
+    fn literal(&mut self, c: Cursor, morpheme: &'static str) -> Vec<Cursor> {
+        let res = self.advance(c, morpheme);
+        if res.is_empty() && (!c.elided || starts_with_merging_vowel(morpheme)) {
+            self.record_failure(c.pos, Some(morpheme));
+        }
+        res
+    }
+
+    // `literal` without recording failures
+    fn advance(&self, c: Cursor, morpheme: &'static str) -> Vec<Cursor> {
+        let mut res = Vec::new();
+        if c.elided && !starts_with_merging_vowel(morpheme) {
+            // a vowel can only be removed if the next morpheme starts with a vowel
+            return res;
+        }
+        let drops_vowel = c.prev == Some(b'i') && matches!(morpheme.as_bytes()[0], b'i' | b'u');
+        let bodies = if drops_vowel {
+            &[morpheme, &morpheme[1..]][..]
+        } else {
+            &[morpheme][..]
+        };
+        let rest = &self.input[c.pos..];
+        let last = *morpheme.as_bytes().last().unwrap();
+        for &body in bodies {
+            if rest.starts_with(body) {
+                res.push(Cursor {
+                    pos: c.pos + body.len(),
+                    prev: Some(last),
+                    elided: false,
+                });
+            }
+            if matches!(last, b'a' | b'o') && rest.starts_with(&body[..body.len() - 1]) {
+                res.push(Cursor {
+                    pos: c.pos + body.len() - 1,
+                    prev: Some(last),
+                    elided: true,
+                });
+            }
+        }
+        res
+    }
+
+    fn name(&mut self, c: Cursor) -> Parses {
+        self.memoized(Rule::Name, c, Self::parse_name)
+    }
+
+    fn parse_name(&mut self, c: Cursor) -> Parses {
+        let mut res = Vec::new();
+        for c in self.literal(c, "nullary") {
+            res.push((Value::Integer(Some(0)), c));
+        }
+        for c in self.literal(c, "unary") {
+            res.push((Value::Integer(Some(1)), c));
+        }
+        for &root in Root::ALL.iter() {
+            for c in self.literal(c, root.name()) {
+                res.push((root_value(root), c));
+            }
+        }
+        for c in self.literal(c, "nega") {
+            for (n, c) in self.name(c) {
+                res.push((n.map(i128::checked_neg), c));
+            }
+        }
+        for c in self.literal(c, "un") {
+            for (one_below, c) in self.name(c) {
+                res.push((one_below.map(|n| n.checked_add(1)), c));
+            }
+        }
+        for c in self.literal(c, "vot") {
+            for (den, c) in self.name(c) {
+                res.push((Value::vot(Value::Integer(Some(1)), den), c));
+            }
+        }
+        res.extend(self.factors(c));
+        res
+    }
+
+    // One or more prefixes, followed by the last factor or by "vot" and the
+    // denominator. Parsing the prefixes together with what follows them, rather
+    // than on their own, means there's only a parse for each way the name can
+    // end, rather than one for each prefix in a long run of them.
+    fn factors(&mut self, c: Cursor) -> Parses {
+        self.memoized(Rule::Factors, c, |parser, c| {
+            let mut res = Vec::new();
+            for (a, c) in parser.prefix(c) {
+                for (b, c) in parser.factors(c) {
+                    res.push((Value::prefix(a, b), c));
+                }
+                for c in parser.literal(c, "vot") {
+                    for (den, c) in parser.name(c) {
+                        res.push((Value::vot(a, den), c));
+                    }
+                }
+                for (b, c) in parser.suffix(c) {
+                    res.push((Value::factor_pair(a, b), c));
+                }
+            }
+            res
+        })
+    }
+
+    fn prefix(&mut self, c: Cursor) -> Parses {
+        self.memoized(Rule::Prefix, c, |parser, c| {
+            let mut res = Vec::new();
+            for &root in Root::ALL.iter() {
+                for c in parser.literal(c, root.prefix_name()) {
+                    res.push((root_value(root), c));
+                }
+            }
+            for c in parser.literal(c, "hen") {
+                for (one_below, c) in parser.hen_factors(c) {
+                    res.push((one_below.map(|n| n.checked_add(1)), c));
+                }
+            }
+            res
+        })
+    }
+
+    // one or more prefixes followed by "sna"
+    fn hen_factors(&mut self, c: Cursor) -> Parses {
+        self.memoized(Rule::HenFactors, c, |parser, c| {
+            let mut res = Vec::new();
+            for (a, c) in parser.prefix(c) {
+                for c in parser.literal(c, "sna") {
+                    res.push((a, c));
+                }
+                for (b, c) in parser.hen_factors(c) {
+                    res.push((Value::factor_pair(a, b), c));
+                }
+            }
+            res
+        })
+    }
+
+    // the last factor of a factor pair
+    fn suffix(&mut self, c: Cursor) -> Parses {
+        self.memoized(Rule::Suffix, c, |parser, c| {
+            let mut res = Vec::new();
+            for &root in Root::ALL.iter() {
+                for c in parser.literal(c, root.suffix_name()) {
+                    res.push((root_value(root), c));
+                }
+            }
+            for c in parser.literal(c, "un") {
+                for (one_below, c) in parser.name(c) {
+                    res.push((one_below.map(|n| n.checked_add(1)), c));
+                }
+            }
+            res
+        })
+    }
+}
+
+// The tree `Base::new` builds for n. Only bases that fit in a `u64` are
+// checked, because larger ones can take very long to factor.
+fn integer_base(n: Option<i128>, cache: &mut Cache) -> Result<Base, ParseError> {
+    let n = n.ok_or(ParseError::TooLarge)?;
+    let abs = u64::try_from(n.unsigned_abs()).map_err(|_| ParseError::TooLarge)?;
+    let base = Base::new_unsigned(abs, cache);
+    Ok(if n < 0 {
+        Base::Nega(Box::new(base))
+    } else {
+        base
+    })
+}
+
+// The tree this crate builds for the value of a parse
+fn canonical(value: Value, cache: &mut Cache) -> Result<Base, ParseError> {
+    match value {
+        Value::Integer(n) => integer_base(n, cache),
+        Value::Vot(num, den) => {
+            // the same restrictions as `Base::new_frac`
+            if num.is_some_and(|num| num < 1) || den.is_some_and(|den| den == 0 || den == 1) {
+                return Err(ParseError::NonCanonical);
+            }
+            Ok(Base::Vot(
+                Box::new(integer_base(num, cache)?),
+                Box::new(integer_base(den, cache)?),
+            ))
+        }
+        Value::Other => Err(ParseError::NonCanonical),
+    }
+}
+
+// The morphemes of a name, mirroring `Base::format_name`
+fn name_morphemes(base: &Base, res: &mut Vec<&'static str>) {
+    match base {
+        Base::Nullary => res.push("nullary"),
+        Base::Unary => res.push("unary"),
+        Base::Root(r) => res.push(r.name()),
+        Base::FactorPair(a, b) => {
+            prefix_morphemes(a, res);
+            match &**b {
+                Base::Root(r) => res.push(r.suffix_name()),
+                b => name_morphemes(b, res),
+            }
+        }
+        Base::Prime(one_below) => {
+            res.push("un");
+            name_morphemes(one_below, res);
+        }
+        Base::Nega(n) => {
+            res.push("nega");
+            name_morphemes(n, res);
+        }
+        Base::Vot(a, b) => {
+            if **a != Base::Unary {
+                prefix_morphemes(a, res);
+            }
+            res.push("vot");
+            name_morphemes(b, res);
+        }
+        // can't happen for canonical trees of rational bases
+        Base::CustomLessThanSix(_) | Base::Imal(_) | Base::Al(_) => (),
+    }
+}
+
+// mirrors `Base::prefix_name`
+fn prefix_morphemes(base: &Base, res: &mut Vec<&'static str>) {
+    match base {
+        Base::Root(r) => res.push(r.prefix_name()),
+        Base::Prime(one_below) => {
+            res.push("hen");
+            prefix_morphemes(one_below, res);
+            res.push("sna");
+        }
+        Base::FactorPair(a, b) => {
+            prefix_morphemes(a, res);
+            prefix_morphemes(b, res);
+        }
+        // can't happen for canonical trees
+        _ => (),
+    }
+}
+
+// Whether `s` is a spelling of the name of `base`, with or without merged
+// vowels. The same value can often be parsed in several ways (e.g.
+// "heptabiseptimal" as 7 * 14 or 14 * 7, or "bibinary" as 4), so this checks
+// the name `base` actually has.
+fn spells(s: &str, base: &Base) -> bool {
+    let mut morphemes = Vec::new();
+    name_morphemes(base, &mut morphemes);
+    let mut parser = Parser::new(s);
+    let mut cursors = vec![Cursor::start()];
+    for morpheme in morphemes {
+        let mut next = Vec::new();
+        for &c in &cursors {
+            next.extend(parser.literal(c, morpheme));
+        }
+        next.sort_unstable();
+        next.dedup();
+        cursors = next;
+    }
+    cursors.iter().any(|c| c.pos == s.len() && !c.elided)
+}
+
+/// Parse a base name (as output by `BaseName`) back into a `Base`
+///
+/// Only canonical names are accepted, and the returned tree is the one
+/// `Base::new` (or `Base::new_frac`) builds for the base.
+pub(crate) fn parse_base(s: &str) -> Result<Base, ParseError> {
+    parse_with(s, |_| true)
+}
+
+/// Parse a base name into an integer that fits in an `i64`
+///
+/// Names of other bases are rejected with `ParseError::Unrepresentable`
+/// before checking that they're canonical, which can take long for large
+/// bases.
+pub(crate) fn parse_integer(s: &str) -> Result<i64, ParseError> {
+    parse_with(s, |value| value.to_i64().is_some())?
+        .to_integer()
+        .ok_or(ParseError::Unrepresentable)
+}
+
+// Parse `s`, only checking that the parses `accept` allows are canonical
+fn parse_with(s: &str, accept: fn(Value) -> bool) -> Result<Base, ParseError> {
+    let mut parser = Parser::new(s);
+    parser.prime();
+    let mut values = Vec::new();
+    for (value, c) in parser.name(Cursor::start()) {
+        if c.pos == s.len() && !c.elided {
+            values.push(value);
+        } else if !c.elided {
+            // a complete name, followed by something else
+            parser.record_failure(c.pos, None);
+        }
+    }
+    if values.is_empty() {
+        return Err(ParseError::InvalidName {
+            offset: parser.furthest,
+            found: unmatched_token(&s[parser.furthest..]).to_string(),
+            expected: parser.expected,
+        });
+    }
+    let mut cache = Cache::default();
+    let mut error = ParseError::NonCanonical;
+    for value in values {
+        if !accept(value) {
+            error = ParseError::Unrepresentable;
+            continue;
+        }
+        match canonical(value, &mut cache) {
+            Ok(base) if spells(s, &base) => return Ok(base),
+            Ok(_) => (),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        base::{BaseName, BaseValue},
+        cache::Cache,
+    };
+
+    #[track_caller]
+    fn check_parse(s: &str, n: i64) {
+        assert_eq!(parse_base(s).unwrap().to_integer(), Some(n));
+    }
+
+    #[test]
+    fn roots() {
+        check_parse("binary", 2);
+        check_parse("baker's dozenal", 13);
+        check_parse("hex", 16);
+        check_parse("centesimal", 100);
+        check_parse("nullary", 0);
+        check_parse("unary", 1);
+    }
+
+    #[test]
+    fn factors_and_primes() {
+        check_parse("hexagesimal", 60);
+        check_parse("biker's dozenal", 26);
+        check_parse("untriseximal", 19);
+        check_parse("hentrihexasnabisuboptimal", 646);
+        check_parse("henbihentetraheptasnasnabintetraker's dozenal", 6254);
+        check_parse("binbinbinbinbinbinoctelevenary", 5758);
+    }
+
+    #[test]
+    fn fixed_vowels() {
+        check_parse("tetroctal", 32);
+        check_parse("octoctal", 64);
+        check_parse("icosioctoctal", 20 * 64);
+        check_parse("icosicosinicosivigesimal", 20 * 20 * 401);
+        check_parse("hepticosiheptavigesimal", 140 * 140);
+        check_parse("icosihecticosicentesimal", 2000 * 2000);
+        check_parse("tetruntriseximal", 4 * 19);
+    }
+
+    #[test]
+    fn unfixed_vowels() {
+        check_parse("tetraoctal", 32);
+        check_parse("octooctal", 64);
+        check_parse("icosiicosiunicosivigesimal", 20 * 20 * 401);
+        check_parse("biuntriseximal", 38);
+        check_parse("tetrauntriseximal", 4 * 19);
+        check_parse("negaoctal", -8);
+        // mixing both spellings is fine too
+        check_parse("icosiicosinicosivigesimal", 20 * 20 * 401);
+    }
+
+    #[test]
+    fn roundtrip_unfixed() {
+        let mut cache = Cache::default();
+        for n in 0..1000 {
+            let base = Base::new(n, &mut cache);
+            let name = BaseName(base, false).to_string();
+            assert_eq!(parse_base(&name).unwrap().to_integer(), Some(n));
+        }
+    }
+
+    #[test]
+    fn nega_and_vot() {
+        check_parse("negabinary", -2);
+        check_parse("negoctal", -8);
+        assert_eq!(
+            parse_base("votdecimal").unwrap().value(),
+            Some(BaseValue::Rational(1, 10))
+        );
+        assert_eq!(
+            parse_base("bivottrinary").unwrap().value(),
+            Some(BaseValue::Rational(2, 3))
+        );
+        assert_eq!(
+            parse_base("bivotnegabinary").unwrap().value(),
+            Some(BaseValue::Integer(-1))
+        );
+    }
+
+    #[test]
+    fn canonical_trees() {
+        let mut cache = Cache::default();
+        for n in -200..3000 {
+            let name = BaseName(Base::new(n, &mut cache), true);
+            assert_eq!(
+                &parse_base(&name.to_string()).unwrap(),
+                name.base(),
+                "{}",
+                n
+            );
+        }
+        for (num, den) in [(1, 10), (2, 3), (4, 2), (6, -7), (19, 98)] {
+            let base = Base::new_frac(num, den, &mut cache).unwrap();
+            let name = BaseName(base.clone(), true).to_string();
+            assert_eq!(parse_base(&name).unwrap(), base);
+        }
+        assert_eq!(
+            parse_base("heptabiseptimal").unwrap(),
+            Base::FactorPair(
+                Box::new(Base::Root(Root::Septimal)),
+                Box::new(Base::FactorPair(
+                    Box::new(Base::Root(Root::Binary)),
+                    Box::new(Base::Root(Root::Septimal))
+                ))
+            )
+        );
+    }
+
+    #[test]
+    fn non_canonical_names() {
+        for name in [
+            "unoctal",
+            "bibinary",
+            "neganullary",
+            "bivotunary",
+            "bivotnullary",
+            "negavotdecimal",
+            "negaunbinary",
+        ] {
+            assert_eq!(parse_base(name), Err(ParseError::NonCanonical), "{}", name);
+        }
+        assert_eq!(
+            ParseError::NonCanonical.to_string(),
+            "not the canonical name of its base"
+        );
+    }
+
+    #[test]
+    fn invalid_names() {
+        assert!(parse_base("").is_err());
+        assert!(parse_base("tetrseximal").is_err());
+        assert!(parse_base("hexadecimal").is_err());
+        assert!(parse_base("phinary").is_err());
+        assert!(parse_base("binaryy").is_err());
+    }
+
+    #[track_caller]
+    fn check_error(s: &str, offset: usize, found: &str) -> Vec<&'static str> {
+        match parse_base(s) {
+            Err(ParseError::InvalidName {
+                offset: o,
+                found: f,
+                expected,
+            }) => {
+                assert_eq!((o, f.as_str()), (offset, found));
+                expected
+            }
+            _ => panic!("expected {:?} to be invalid", s),
+        }
+    }
+
+    #[test]
+    fn error_positions() {
+        let expected = check_error("tetrseximal", 4, "seximal");
+        assert!(expected.contains(&"octal"));
+        assert!(expected.contains(&"un"));
+        assert!(!expected.contains(&"seximal"));
+
+        let expected = check_error("binaryy", 6, "y");
+        assert!(expected.is_empty());
+
+        let expected = check_error("", 0, "");
+        assert!(expected.contains(&"nullary"));
+        assert!(expected.contains(&"hen"));
+
+        let expected = check_error("hentrihexabisuboptimal", 12, "suboptimal");
+        assert!(expected.contains(&"sna"));
+
+        // "mal" is the prefix for suboptimal
+        check_error("hexadecimal", 7, "i");
+        check_error("xyzbinary", 0, "xyz");
+        check_error("seximalbinary", 7, "binary");
+        check_error("bibinaryfoohex", 8, "foo");
+        check_error("tetrtetroctal", 4, "tetr");
+    }
+
+    #[test]
+    fn too_large() {
+        // 2^64
+        assert_eq!(
+            parse_base(&("octo".repeat(21) + "binary")),
+            Err(ParseError::TooLarge)
+        );
+        // larger than 2^127
+        assert_eq!(
+            parse_base(&("oct".repeat(42) + "octal")),
+            Err(ParseError::TooLarge)
+        );
+        let base = Base::new_unsigned(u64::MAX, &mut Cache::default());
+        let name = BaseName(base.clone(), true).to_string();
+        assert_eq!(parse_base(&name), Ok(base));
+    }
+
+    #[test]
+    fn long_names() {
+        let name = "bi".repeat(2000) + "binary";
+        assert_eq!(parse_base(&name), Err(ParseError::TooLarge));
+        let name = "un".repeat(1000) + "binary";
+        assert_eq!(parse_base(&name), Err(ParseError::NonCanonical));
+        let name = "nega".repeat(1000) + "binary";
+        assert_eq!(parse_base(&name), Err(ParseError::NonCanonical));
+        let name = "hen".repeat(500) + "bi" + &"sna".repeat(500) + "binary";
+        assert!(parse_base(&name).is_err());
+    }
+
+    #[test]
+    fn roundtrip_negative() {
+        let mut cache = Cache::default();
+        for n in -300..0 {
+            let name = BaseName(Base::new(n, &mut cache), true).to_string();
+            assert_eq!(parse_base(&name).unwrap().to_integer(), Some(n));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::convert::TryFrom;
+
+// Static tables for small bases, built by build.rs if the `precomputed`
+// feature is enabled. `SMALLER_FACTORS` and `ABBREVIATIONS` are indexed by
+// the base, starting from 0, and `BASES` has every abbreviation with its base,
+// sorted by abbreviation.
+#[cfg(feature = "precomputed")]
+include!(concat!(env!("OUT_DIR"), "/precomputed.rs"));
+
+#[cfg(not(feature = "precomputed"))]
+static SMALLER_FACTORS: [u32; 0] = [];
+#[cfg(not(feature = "precomputed"))]
+static ABBREVIATIONS: [&str; 0] = [];
+#[cfg(not(feature = "precomputed"))]
+static BASES: [(&str, u32); 0] = [];
+
+/// The largest base in the precomputed tables, or `None` if the
+/// `precomputed` feature isn't enabled
+pub fn precomputed_limit() -> Option<u64> {
+    (ABBREVIATIONS.len() as u64).checked_sub(1)
+}
+
+// The smaller of the closest factors of n (1 for primes), see `closest_factors`
+pub(crate) fn smaller_factor(n: u64) -> Option<u64> {
+    if n < 2 {
+        return None;
+    }
+    let i = usize::try_from(n).ok()?;
+    SMALLER_FACTORS.get(i).map(|&f| f.into())
+}
+
+pub(crate) fn abbreviation(n: usize) -> Option<&'static str> {
+    ABBREVIATIONS.get(n).copied()
+}
+
+// The base with the given abbreviation, if it's in the table
+pub(crate) fn base(abbr: &str) -> Option<usize> {
+    let i = BASES.binary_search_by_key(&abbr, |&(a, _)| a).ok()?;
+    usize::try_from(BASES[i].1).ok()
+}
+
+/// Get the abbreviation of the given number base from the precomputed table,
+/// without a `Cache`
+///
+/// This gives the same results as `base_abbreviation`, e.g. "NUL" for 0 and
+/// "-DEC" for -10, but returns `None` if the absolute value of the number is
+/// above `precomputed_limit` (so always, without the `precomputed` feature).
+///
+/// Larger bases can't be abbreviated without calculating the abbreviations
+/// of every base before them: each abbreviation is chosen to differ from the
+/// abbreviations of all smaller bases, so any scheme that avoided this would
+/// give different abbreviations. Instead, `base_abbreviation` continues from
+/// the end of the precomputed table, and a cache saved with `Cache::save` can
+/// be restored with `Cache::load` to continue from where it stopped. There
+/// are no checkpoints beyond the table, so abbreviating bases far above
+/// `precomputed_limit` is out of scope.
+pub fn precomputed_abbreviation(number: i64) -> Option<String> {
+    let abbr = abbreviation(usize::try_from(number.unsigned_abs()).ok()?)?;
+    Some(if number < 0 {
+        format!("-{}", abbr)
+    } else {
+        abbr.to_string()
+    })
+}
+
+// The smallest base that isn't in the precomputed tables, for tests that need
+// the cache to be used
+#[cfg(test)]
+pub(crate) fn first_uncached() -> i64 {
+    precomputed_limit().map_or(0, |n| n as i64 + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit() {
+        assert_eq!(precomputed_limit().is_some(), cfg!(feature = "precomputed"));
+    }
+
+    #[test]
+    fn stateless_abbreviations() {
+        use crate::{base_abbreviation, Cache};
+
+        assert_eq!(precomputed_abbreviation(i64::MAX), None);
+        assert_eq!(precomputed_abbreviation(i64::MIN), None);
+        let Some(limit) = precomputed_limit() else {
+            assert_eq!(precomputed_abbreviation(0), None);
+            return;
+        };
+        let limit = limit as i64;
+        assert_eq!(precomputed_abbreviation(0).unwrap(), "NUL");
+        assert_eq!(precomputed_abbreviation(-10).unwrap(), "-DEC");
+        let mut cache = Cache::default();
+        for n in -limit..=limit {
+            assert_eq!(
+                precomputed_abbreviation(n),
+                Some(base_abbreviation(n, &mut cache))
+            );
+        }
+        assert_eq!(precomputed_abbreviation(limit + 1), None);
+        assert_eq!(precomputed_abbreviation(-limit - 1), None);
+    }
+
+    #[test]
+    fn reverse_lookup() {
+        for (n, &abbr) in ABBREVIATIONS.iter().enumerate() {
+            assert_eq!(base(abbr), Some(n));
+        }
+        assert_eq!(base("DEC").is_some(), cfg!(feature = "precomputed"));
+        assert_eq!(base("dec"), None);
+        assert_eq!(base(""), None);
+    }
+
+    #[cfg(feature = "precomputed")]
+    #[test]
+    fn skips_cache() {
+        use crate::{base_abbreviation, base_name, Cache};
+
+        let limit = precomputed_limit().unwrap() as i64;
+        let mut cache = Cache::default();
+        assert!(!base_name(limit, &mut cache).to_string().is_empty());
+        base_abbreviation(limit, &mut cache);
+        let stats = cache.stats();
+        assert_eq!((stats.factor_hits, stats.factor_misses), (0, 0));
+        assert_eq!(stats.abbreviation_entries, 0);
+
+        // bases above the limit continue from the precomputed abbreviations
+        base_abbreviation(limit + 1, &mut cache);
+        assert_eq!(cache.stats().abbreviation_entries as i64, limit + 2);
+    }
+}
@@ -0,0 +1,492 @@
+use crate::{
+    abbreviation::{AbbreviationOptions, VowelPolicy},
+    base::{closest_factors, num_roots_in_name, Root},
+    cache::{Cache, CachePolicy, Memo},
+};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    error, fmt,
+    io::{self, BufReader, BufWriter, Read, Write},
+};
+
+// Snapshot format, all integers except the version are LEB128 varints, and
+// strings are written as their length followed by their UTF-8 bytes:
+//
+//   "BASESNAP", version (u32, little endian)
+//   number of factor pairs, then (n, smaller factor) for each
+//   number of root counts, then (n, prefix as 0 or 1, count) for each
+//   the abbreviation options (since version 2):
+//     minimum length
+//     vowel policy: 0 to keep vowels, or 1 and the number of characters
+//       after which they are dropped
+//     alphabet, then number of reserved abbreviations and each of them
+//   number of abbreviations, then each abbreviation, from 0 up
+//
+// Version 1 snapshots don't have options, they always use the defaults.
+const MAGIC: &[u8; 8] = b"BASESNAP";
+const VERSION: u32 = 2;
+
+/// Error returned when a `Cache` snapshot can't be loaded
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SnapshotError {
+    /// Reading the snapshot failed
+    Io(io::Error),
+    /// The data doesn't start like a snapshot
+    NotASnapshot,
+    /// The snapshot was saved by an incompatible version of this crate
+    UnsupportedVersion(u32),
+    /// The snapshot is truncated or contains invalid data
+    Corrupt,
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read snapshot: {}", e),
+            Self::NotASnapshot => write!(f, "not a cache snapshot"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported snapshot version {}", v),
+            Self::Corrupt => write!(f, "snapshot is corrupt"),
+        }
+    }
+}
+
+impl error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            Self::Corrupt
+        } else {
+            Self::Io(e)
+        }
+    }
+}
+
+fn write_varint(w: &mut impl Write, mut n: u64) -> io::Result<()> {
+    while n >= 0x80 {
+        w.write_all(&[n as u8 | 0x80])?;
+        n >>= 7;
+    }
+    w.write_all(&[n as u8])
+}
+
+fn read_varint(r: &mut impl Read) -> Result<u64, SnapshotError> {
+    let mut res = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        r.read_exact(&mut byte)?;
+        let bits = u64::from(byte[0] & 0x7f);
+        if bits << shift >> shift != bits {
+            return Err(SnapshotError::Corrupt);
+        }
+        res |= bits << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(res);
+        }
+    }
+    Err(SnapshotError::Corrupt)
+}
+
+fn read_usize(r: &mut impl Read) -> Result<usize, SnapshotError> {
+    usize::try_from(read_varint(r)?).map_err(|_| SnapshotError::Corrupt)
+}
+
+fn write_string(w: &mut impl Write, s: &str) -> io::Result<()> {
+    write_varint(w, s.len() as u64)?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_string(r: &mut impl Read) -> Result<String, SnapshotError> {
+    let len = read_varint(r)?;
+    // the buffer only grows as the bytes arrive, so a bad length can't cause
+    // a huge allocation
+    let mut s = vec![];
+    r.take(len).read_to_end(&mut s)?;
+    if s.len() as u64 != len {
+        return Err(SnapshotError::Corrupt);
+    }
+    String::from_utf8(s).map_err(|_| SnapshotError::Corrupt)
+}
+
+fn write_options(w: &mut impl Write, options: &AbbreviationOptions) -> io::Result<()> {
+    write_varint(w, options.min_len as u64)?;
+    match options.vowels {
+        VowelPolicy::Keep => write_varint(w, 0)?,
+        VowelPolicy::DropAfter(n) => {
+            write_varint(w, 1)?;
+            write_varint(w, n as u64)?;
+        }
+    }
+    write_string(w, &options.alphabet)?;
+    write_varint(w, options.reserved.len() as u64)?;
+    for abbr in &options.reserved {
+        write_string(w, abbr)?;
+    }
+    Ok(())
+}
+
+fn read_options(r: &mut impl Read) -> Result<AbbreviationOptions, SnapshotError> {
+    let min_len = read_usize(r)?;
+    let vowels = match read_varint(r)? {
+        0 => VowelPolicy::Keep,
+        1 => VowelPolicy::DropAfter(read_usize(r)?),
+        _ => return Err(SnapshotError::Corrupt),
+    };
+    let mut options = AbbreviationOptions::default()
+        .min_len(min_len)
+        .vowels(vowels)
+        .alphabet(read_string(r)?);
+    for _ in 0..read_varint(r)? {
+        options = options.reserve(read_string(r)?);
+    }
+    Ok(options)
+}
+
+fn is_root(n: u64) -> bool {
+    i64::try_from(n).ok().and_then(Root::from_number).is_some()
+}
+
+impl Cache {
+    /// Saves the memoized factors, root counts and abbreviations, so that they
+    /// can be restored later with `Cache::load`. Cache statistics aren't saved.
+    pub fn save(&self, writer: impl Write) -> io::Result<()> {
+        let mut w = BufWriter::new(writer);
+        w.write_all(MAGIC)?;
+        w.write_all(&VERSION.to_le_bytes())?;
+
+        // sorted, so that equal caches produce identical snapshots
+        let mut factors: Vec<_> = self.tables.factors.iter().collect();
+        factors.sort_unstable();
+        write_varint(&mut w, factors.len() as u64)?;
+        for (&n, &(smaller, _)) in factors {
+            write_varint(&mut w, n)?;
+            write_varint(&mut w, smaller)?;
+        }
+
+        let mut root_counts: Vec<_> = self.tables.root_counts.iter().collect();
+        root_counts.sort_unstable();
+        write_varint(&mut w, root_counts.len() as u64)?;
+        for (&(n, prefix), &count) in root_counts {
+            write_varint(&mut w, n)?;
+            write_varint(&mut w, prefix.into())?;
+            write_varint(&mut w, count as u64)?;
+        }
+
+        write_options(&mut w, &self.abbreviations.options)?;
+        let abbreviations = &self.abbreviations.abbreviations;
+        write_varint(&mut w, abbreviations.len() as u64)?;
+        for abbr in abbreviations {
+            write_string(&mut w, abbr)?;
+        }
+        w.flush()
+    }
+
+    /// Restores a cache saved with `Cache::save`
+    ///
+    /// The `CachePolicy` isn't saved, the restored cache is always unbounded.
+    /// Use `Cache::load_with_policy` to limit it. Factors and root counts are
+    /// checked against the ones this crate calculates, and snapshots with any
+    /// that differ are rejected as `SnapshotError::Corrupt`.
+    pub fn load(reader: impl Read) -> Result<Self, SnapshotError> {
+        Self::load_with_policy(reader, CachePolicy::Unbounded)
+    }
+
+    /// Restores a cache saved with `Cache::save`, which then remembers as much
+    /// as the given policy allows
+    ///
+    /// If the snapshot has more entries than that, the entries for the
+    /// largest bases are kept. Abbreviations are always restored in full.
+    pub fn load_with_policy(reader: impl Read, policy: CachePolicy) -> Result<Self, SnapshotError> {
+        let mut r = BufReader::new(reader);
+        let mut header = [0; 12];
+        r.read_exact(&mut header).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => SnapshotError::NotASnapshot,
+            _ => SnapshotError::Io(e),
+        })?;
+        if &header[..8] != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let version = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        if version == 0 || version > VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let mut cache = Self::with_policy(policy);
+        let mut factors = HashMap::new();
+        for _ in 0..read_varint(&mut r)? {
+            let n = read_varint(&mut r)?;
+            let smaller = read_varint(&mut r)?;
+            if n < 2 || smaller == 0 || n % smaller != 0 || smaller > n / smaller {
+                return Err(SnapshotError::Corrupt);
+            }
+            factors.insert(n, smaller);
+        }
+
+        // kept in order, so that the same entries are evicted every time
+        let mut root_counts = vec![];
+        for _ in 0..read_varint(&mut r)? {
+            let n = read_varint(&mut r)?;
+            let prefix = match read_varint(&mut r)? {
+                0 => false,
+                1 => true,
+                _ => return Err(SnapshotError::Corrupt),
+            };
+            let count = read_usize(&mut r)?;
+            // roots aren't cached, and every other base has at least two
+            if n < 2 || is_root(n) || count < 2 {
+                return Err(SnapshotError::Corrupt);
+            }
+            root_counts.push(((n, prefix), count));
+        }
+        // A factor pair or root count that's valid on its own but isn't the
+        // one this crate would choose would silently produce wrong names, so
+        // every entry is calculated again. Going from the smallest base up,
+        // the entries each base depends on are already known by the time
+        // it's checked, so this takes a few lookups per entry rather than
+        // naming everything from scratch.
+        let root_count_map: HashMap<_, _> = root_counts.iter().copied().collect();
+        let mut bases: Vec<_> = factors
+            .keys()
+            .copied()
+            .chain(root_counts.iter().map(|&((n, _), _)| n))
+            .collect();
+        bases.sort_unstable();
+        bases.dedup();
+        let mut check = Cache::default();
+        for n in bases {
+            if factors
+                .get(&n)
+                .is_some_and(|&smaller| closest_factors(n, &mut check).0 != smaller)
+            {
+                return Err(SnapshotError::Corrupt);
+            }
+            for prefix in [false, true] {
+                if root_count_map
+                    .get(&(n, prefix))
+                    .is_some_and(|&count| num_roots_in_name(n, prefix, &mut check) != count)
+                {
+                    return Err(SnapshotError::Corrupt);
+                }
+            }
+        }
+        let mut factors: Vec<_> = factors.into_iter().collect();
+        factors.sort_unstable();
+        for (n, smaller) in factors {
+            cache.insert_factors(n, (smaller, n / smaller));
+        }
+        for ((n, prefix), count) in root_counts {
+            cache.insert_root_count((n, prefix), count);
+        }
+
+        if version >= 2 {
+            cache.set_abbreviation_options(read_options(&mut r)?);
+        }
+        let table = &mut cache.abbreviations;
+        for n in 0..read_varint(&mut r)? {
+            let abbr = read_string(&mut r)?;
+            // abbreviations are made of characters of the alphabet, unless
+            // they fall back to the number, and each one is only used once
+            let options = &table.options;
+            let letters = !abbr.is_empty() && abbr.chars().all(|c| options.alphabet.contains(c));
+            if !(letters || abbr.trim_end_matches('\'') == n.to_string())
+                || options.is_reserved(&abbr)
+                || table.bases.contains_key(&abbr)
+            {
+                return Err(SnapshotError::Corrupt);
+            }
+            table.push(abbr);
+        }
+        Ok(cache)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{base_abbreviation, base_name, precomputed::first_uncached};
+
+    fn warm_cache() -> Cache {
+        let mut cache = Cache::default();
+        let start = first_uncached();
+        for n in start..start + 2000 {
+            base_name(n, &mut cache);
+        }
+        base_abbreviation(start + 1000, &mut cache);
+        cache
+    }
+
+    fn snapshot(cache: &Cache) -> Vec<u8> {
+        let mut res = vec![];
+        cache.save(&mut res).unwrap();
+        res
+    }
+
+    #[test]
+    fn roundtrip() {
+        let cache = warm_cache();
+        let bytes = snapshot(&cache);
+        let mut loaded = Cache::load(&bytes[..]).unwrap();
+        let (stats, loaded_stats) = (cache.stats(), loaded.stats());
+        assert_eq!(loaded_stats.factor_entries, stats.factor_entries);
+        assert_eq!(loaded_stats.root_count_entries, stats.root_count_entries);
+        let start = first_uncached();
+        assert_eq!(loaded_stats.abbreviation_entries as i64, start + 1001);
+        assert_eq!(snapshot(&loaded), bytes);
+
+        // everything that was saved is used without recalculating it
+        let mut fresh = Cache::default();
+        assert_eq!(
+            base_name(646, &mut loaded).to_string(),
+            "hentrihexasnabisuboptimal"
+        );
+        assert_eq!(
+            base_name(start + 646, &mut loaded).to_string(),
+            base_name(start + 646, &mut fresh).to_string()
+        );
+        assert_eq!(base_abbreviation(585, &mut loaded), "BAKR");
+        assert_eq!(loaded.stats().factor_misses, 0);
+
+        for n in start + 1000..start + 1500 {
+            assert_eq!(
+                base_abbreviation(n, &mut loaded),
+                base_abbreviation(n, &mut fresh)
+            );
+        }
+    }
+
+    #[test]
+    fn empty_cache() {
+        let bytes = snapshot(&Cache::default());
+        assert_eq!(bytes.len(), 46);
+        assert_eq!(Cache::load(&bytes[..]).unwrap().stats().factor_entries, 0);
+    }
+
+    #[test]
+    fn abbreviation_options() {
+        let options = AbbreviationOptions::default()
+            .min_len(4)
+            .vowels(VowelPolicy::Keep)
+            .reserve("SEXI")
+            .alphabet("ABCDEFGHIJKLMNOPQRSTUVWXY");
+        let mut cache = Cache::default();
+        cache.set_abbreviation_options(options.clone());
+        let abbr = base_abbreviation(100, &mut cache);
+        let mut loaded = Cache::load(&snapshot(&cache)[..]).unwrap();
+        assert_eq!(loaded.abbreviation_options(), &options);
+        assert_eq!(base_abbreviation(100, &mut loaded), abbr);
+        assert_eq!(
+            base_abbreviation(200, &mut loaded),
+            base_abbreviation(200, &mut cache)
+        );
+    }
+
+    #[test]
+    fn long_strings() {
+        let reserved = "X".repeat(5000);
+        let mut cache = Cache::default();
+        cache.set_abbreviation_options(AbbreviationOptions::default().reserve(reserved.as_str()));
+        base_abbreviation(10, &mut cache);
+        let loaded = Cache::load(&snapshot(&cache)[..]).unwrap();
+        assert!(loaded.abbreviation_options().is_reserved(&reserved));
+    }
+
+    #[test]
+    fn load_with_policy() {
+        let bytes = snapshot(&warm_cache());
+        let policy = CachePolicy::Lru { max_entries: 100 };
+        let mut loaded = Cache::load_with_policy(&bytes[..], policy).unwrap();
+        let stats = loaded.stats();
+        assert!(stats.factor_entries <= 100 && stats.root_count_entries <= 100);
+        let start = first_uncached();
+        for n in start..start + 3000 {
+            base_name(n, &mut loaded);
+        }
+        assert!(loaded.stats().factor_entries <= 100);
+        assert_eq!(base_abbreviation(585, &mut loaded), "BAKR");
+    }
+
+    #[test]
+    fn version_1() {
+        // saved before abbreviation options were added: no factors or root
+        // counts, and the abbreviations of 0 and 1
+        let mut bytes = b"BASESNAP\x01\0\0\0\0\0\x02".to_vec();
+        bytes.extend_from_slice(b"\x03NUL\x03UNA");
+        let mut cache = Cache::load(&bytes[..]).unwrap();
+        assert!(cache.abbreviation_options().is_default());
+        assert_eq!(cache.stats().abbreviation_entries, 2);
+        assert_eq!(base_abbreviation(2, &mut cache), "BIN");
+    }
+
+    #[test]
+    fn invalid_snapshots() {
+        let bytes = snapshot(&warm_cache());
+        assert!(matches!(
+            Cache::load(&b"not a snapshot"[..]),
+            Err(SnapshotError::NotASnapshot)
+        ));
+        assert!(matches!(
+            Cache::load(&bytes[..4]),
+            Err(SnapshotError::NotASnapshot)
+        ));
+
+        let mut newer = bytes.clone();
+        newer[8] = 3;
+        assert!(matches!(
+            Cache::load(&newer[..]),
+            Err(SnapshotError::UnsupportedVersion(3))
+        ));
+        assert_eq!(
+            Cache::load(&newer[..]).err().unwrap().to_string(),
+            "unsupported snapshot version 3"
+        );
+
+        assert!(matches!(
+            Cache::load(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Corrupt)
+        ));
+
+        // the options and abbreviations of an empty cache
+        let empty = snapshot(&Cache::default());
+        let (header, options) = (&empty[..12], &empty[14..empty.len() - 1]);
+        let load = |factors: &[u8], root_counts: &[u8], abbreviations: &[u8]| {
+            let bytes = [header, factors, root_counts, options, abbreviations].concat();
+            Cache::load(&bytes[..])
+        };
+        assert!(load(&[0], &[0], &[0]).is_ok());
+
+        // a single factor pair claiming that 10 = 3 * 3
+        let wrong = load(&[1, 10, 3], &[0], &[0]);
+        assert!(matches!(wrong, Err(SnapshotError::Corrupt)));
+
+        // 14 = 2 * 7 is bi-septimal, with two roots
+        assert!(load(&[1, 14, 2], &[1, 14, 0, 2], &[0]).is_ok());
+        for root_counts in [[1, 14, 0, 3], [1, 14, 0, 1], [1, 10, 0, 2]] {
+            let wrong = load(&[1, 14, 2], &root_counts, &[0]);
+            assert!(matches!(wrong, Err(SnapshotError::Corrupt)));
+        }
+        // even without its factors, 14 needs two roots
+        let wrong = load(&[0], &[1, 14, 0, 3], &[0]);
+        assert!(matches!(wrong, Err(SnapshotError::Corrupt)));
+
+        // 24 = 2 * 12 is a valid factor pair, but 24 is named as 4 * 6
+        assert!(load(&[1, 24, 4], &[0], &[0]).is_ok());
+        let wrong = load(&[1, 24, 2], &[0], &[0]);
+        assert!(matches!(wrong, Err(SnapshotError::Corrupt)));
+
+        assert!(load(&[0], &[0], b"\x02\x03NUL\x011").is_ok());
+        assert!(load(&[0], &[0], b"\x02\x03NUL\x021'").is_ok());
+        for abbreviations in [&b"\x02\x03NUL\x03NUL"[..], b"\x01\x03nul", b"\x01\0"] {
+            let wrong = load(&[0], &[0], abbreviations);
+            assert!(matches!(wrong, Err(SnapshotError::Corrupt)));
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    abbreviation::AbbreviationOptions,
+    alternatives::{alternative_names_with_memo, AlternativeNames},
+    base::{find_base, integer_abbreviation, rational_abbreviation, Base, BaseName, NameError},
+    cache::{AbbreviationTable, CachePolicy, CacheStats, Memo, MemoMap},
+    explain::{explain_with_memo, Explanation},
+    integer::BaseInteger,
+};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hash},
+    string::String,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    vec::Vec,
+};
+
+const SHARDS: usize = 16;
+
+// The memoized results never change once they've been inserted, so a
+// poisoned lock still holds valid data
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A cache that can be shared between threads
+///
+/// This works like `Cache`, but its methods only need a shared reference, so
+/// it can be put in a `static` or an `Arc`. The memoized results are split
+/// into several separately locked shards, so threads rarely wait for each
+/// other. Results are always identical to the ones you'd get with `Cache`.
+///
+/// Abbreviations have to be calculated in order, so only one thread at a time
+/// can extend the abbreviation table.
+pub struct SyncCache<T = u64> {
+    hasher: RandomState,
+    factors: Vec<Mutex<MemoMap<T, (T, T)>>>,
+    root_counts: Vec<Mutex<MemoMap<(T, bool), usize>>>,
+    abbreviations: Mutex<AbbreviationTable>,
+    factor_hits: AtomicU64,
+    factor_misses: AtomicU64,
+    root_count_hits: AtomicU64,
+    root_count_misses: AtomicU64,
+}
+
+impl<T> Default for SyncCache<T> {
+    fn default() -> Self {
+        Self::with_policy(CachePolicy::Unbounded)
+    }
+}
+
+impl<T> SyncCache<T> {
+    /// Creates an empty cache that remembers as much as the given policy
+    /// allows. The limit is split evenly between the shards, rounding up, so
+    /// up to 15 more entries than the limit can be kept in each table.
+    pub fn with_policy(policy: CachePolicy) -> Self {
+        let max_len = policy.max_len().map(|n| n.div_ceil(SHARDS));
+        Self {
+            hasher: RandomState::new(),
+            factors: (0..SHARDS)
+                .map(|_| Mutex::new(MemoMap::new(max_len)))
+                .collect(),
+            root_counts: (0..SHARDS)
+                .map(|_| Mutex::new(MemoMap::new(max_len)))
+                .collect(),
+            abbreviations: Mutex::default(),
+            factor_hits: AtomicU64::new(0),
+            factor_misses: AtomicU64::new(0),
+            root_count_hits: AtomicU64::new(0),
+            root_count_misses: AtomicU64::new(0),
+        }
+    }
+
+    fn shard<K: Hash>(&self, key: &K) -> usize {
+        (self.hasher.hash_one(key) % SHARDS as u64) as usize
+    }
+
+    /// Changes how abbreviations are chosen for this cache, see
+    /// `Cache::set_abbreviation_options`
+    pub fn set_abbreviation_options(&self, options: AbbreviationOptions) {
+        let mut table = lock(&self.abbreviations);
+        if options != table.options {
+            *table = AbbreviationTable::new(options);
+        }
+    }
+
+    /// Returns how often the cache was used and how much it holds
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            factor_hits: self.factor_hits.load(Ordering::Relaxed),
+            factor_misses: self.factor_misses.load(Ordering::Relaxed),
+            factor_entries: self.factors.iter().map(|s| lock(s).len()).sum(),
+            root_count_hits: self.root_count_hits.load(Ordering::Relaxed),
+            root_count_misses: self.root_count_misses.load(Ordering::Relaxed),
+            root_count_entries: self.root_counts.iter().map(|s| lock(s).len()).sum(),
+            abbreviation_entries: lock(&self.abbreviations).abbreviations.len(),
+            evictions: (self.factors.iter().map(|s| lock(s).evictions))
+                .chain(self.root_counts.iter().map(|s| lock(s).evictions))
+                .sum(),
+        }
+    }
+}
+
+impl<T: BaseInteger> SyncCache<T> {
+    /// Get the name of a given non-negative number base, see
+    /// `unsigned_base_name`
+    pub fn unsigned_base_name(&self, number: T) -> BaseName {
+        BaseName(Base::new_unsigned(number, &mut &*self), true)
+    }
+}
+
+impl SyncCache {
+    /// Get the name of a given number base, see `base_name`
+    pub fn base_name(&self, number: i64) -> BaseName {
+        BaseName(Base::new(number, &mut &*self), true)
+    }
+
+    /// Get the name of a fractional base, see `try_rational_base_name`
+    pub fn try_rational_base_name(&self, num: i64, den: i64) -> Result<BaseName, NameError> {
+        Ok(BaseName(Base::new_frac(num, den, &mut &*self)?, true))
+    }
+
+    /// Get the abbreviation of a given number base, see `base_abbreviation`
+    pub fn base_abbreviation(&self, number: i64) -> String {
+        let mut table = lock(&self.abbreviations);
+        integer_abbreviation(number, &mut &*self, &mut table)
+    }
+
+    /// Get the abbreviation of a fractional base, see
+    /// `try_rational_base_abbreviation`
+    pub fn try_rational_base_abbreviation(&self, num: i64, den: i64) -> Result<String, NameError> {
+        let mut table = lock(&self.abbreviations);
+        rational_abbreviation(num, den, &mut &*self, &mut table)
+    }
+
+    /// Get the number base with the given abbreviation, see
+    /// `base_from_abbreviation`
+    pub fn base_from_abbreviation(&self, abbr: &str, limit: i64) -> Option<i64> {
+        let mut table = lock(&self.abbreviations);
+        find_base(abbr, limit, &mut &*self, &mut table)
+    }
+
+    /// Explain how the name of a given number base is chosen, see `explain`
+    pub fn explain(&self, number: i64) -> Explanation {
+        explain_with_memo(number, &mut &*self)
+    }
+
+    /// Get every valid name for a given number base, see `alternative_names`
+    pub fn alternative_names(&self, number: i64) -> AlternativeNames {
+        alternative_names_with_memo(number, &mut &*self)
+    }
+}
+
+impl<T: BaseInteger> Memo<T> for &SyncCache<T> {
+    fn factors(&mut self, n: &T) -> Option<(T, T)> {
+        let res = lock(&self.factors[self.shard(n)]).get(n).cloned();
+        if res.is_some() {
+            self.factor_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.factor_misses.fetch_add(1, Ordering::Relaxed);
+        }
+        res
+    }
+
+    fn insert_factors(&mut self, n: T, factors: (T, T)) {
+        lock(&self.factors[self.shard(&n)]).insert(n, factors);
+    }
+
+    fn root_count(&mut self, key: &(T, bool)) -> Option<usize> {
+        let res = lock(&self.root_counts[self.shard(key)]).get(key).copied();
+        if res.is_some() {
+            self.root_count_hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.root_count_misses.fetch_add(1, Ordering::Relaxed);
+        }
+        res
+    }
+
+    fn insert_root_count(&mut self, key: (T, bool), count: usize) {
+        lock(&self.root_counts[self.shard(&key)]).insert(key, count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{base_abbreviation, base_name, explain, precomputed::first_uncached, Cache};
+    use std::{string::ToString, sync::Arc, thread, vec};
+
+    #[test]
+    fn lru_policy() {
+        let mut cache = Cache::default();
+        let sync_cache = SyncCache::with_policy(CachePolicy::Lru { max_entries: 160 });
+        let start = first_uncached();
+        for n in start..start + 3000 {
+            assert_eq!(
+                sync_cache.base_name(n).to_string(),
+                base_name(n, &mut cache).to_string()
+            );
+        }
+        let stats = sync_cache.stats();
+        assert!(stats.factor_entries <= 160 && stats.evictions > 0);
+
+        // limits smaller than the number of shards still keep some entries
+        let sync_cache = SyncCache::with_policy(CachePolicy::Lru { max_entries: 4 });
+        for n in start..start + 100 {
+            sync_cache.base_name(n);
+        }
+        let stats = sync_cache.stats();
+        assert!(stats.factor_entries > 0 && stats.factor_entries <= SHARDS);
+    }
+
+    #[test]
+    fn sync_cache_matches_cache() {
+        let sync_cache = Arc::new(SyncCache::default());
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let sync_cache = Arc::clone(&sync_cache);
+                thread::spawn(move || {
+                    // each thread goes through the numbers in a different order
+                    let mut res = vec![];
+                    let start = first_uncached();
+                    for n in (0..2000).map(|n| start + (n * (2 * i + 1)) % 2000) {
+                        let name = sync_cache.base_name(n).to_string();
+                        let abbr = sync_cache.base_abbreviation(n + 1);
+                        res.push((n, name, abbr));
+                    }
+                    res
+                })
+            })
+            .collect();
+        let mut cache = Cache::default();
+        for handle in handles {
+            for (n, name, abbr) in handle.join().unwrap() {
+                assert_eq!(name, base_name(n, &mut cache).to_string());
+                assert_eq!(abbr, base_abbreviation(n + 1, &mut cache));
+            }
+        }
+        assert_eq!(sync_cache.explain(646), explain(646, &mut cache));
+        assert_eq!(
+            sync_cache.try_rational_base_name(0, 5).err(),
+            Some(NameError::InvalidNumerator)
+        );
+        assert_eq!(sync_cache.base_from_abbreviation("BAKR", 1000), Some(585));
+        assert_eq!(sync_cache.base_abbreviation(-1), "-UNA");
+        assert_eq!(
+            sync_cache.try_rational_base_abbreviation(1, 10).unwrap(),
+            "UNA/DEC"
+        );
+        let stats = sync_cache.stats();
+        assert!(stats.factor_hits > 0);
+        assert_eq!(stats.abbreviation_entries as i64, first_uncached() + 2001);
+    }
+}